@@ -0,0 +1,248 @@
+//
+// # Conformance of the Rust scanner and parser to the corpus shared with the TypeScript front end.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+// The corpus in conformance/ at the top of the repository gives Lligne sources with their expected tokens
+// (scanning.json) and expression trees (parsing.json) in the shapes described in conformance/README.md. Offsets there
+// count UTF-16 code units, as in TypeScript, so the byte offsets of the Rust front end are converted before comparing.
+//
+// A case whose Rust output differs fails the test unless the case lists a "rust" entry under "divergences" explaining
+// the known difference. A listed divergence that no longer occurs also fails, so the list stays accurate. The known
+// divergences themselves fail test_known_divergences, which is ignored so that it shows in every test summary as a
+// reminder; cargo test -- --ignored lists them.
+//
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use crate::lligne::code::parsing::expressions::Expr;
+use crate::lligne::code::parsing::parser;
+use crate::lligne::code::scanning::{scanner, token_types};
+use crate::lligne::code::scanning::token_filters::leading_trailing_documentation;
+use crate::lligne::code::util::line_index::{ColumnUnit, LineIndex, new_line_index};
+use crate::lligne::code::util::source_pos::{new_source_pos, SourcePos};
+
+//=====================================================================================================================
+
+// One case of scanning.json.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanningCase {
+    name: String,
+    source: String,
+    tokens: Value,
+    new_line_count: usize,
+    #[serde(default)]
+    divergences: HashMap<String, String>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// One case of parsing.json.
+#[derive(Deserialize)]
+struct ParsingCase {
+    name: String,
+    source: String,
+    tree: Value,
+    #[serde(default)]
+    divergences: HashMap<String, String>,
+}
+
+//=====================================================================================================================
+
+// Reads one file of cases from the corpus.
+fn read_cases<T: for<'de> Deserialize<'de>>(file_name: &str) -> Vec<T> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..").join("conformance").join(file_name);
+    let text = fs::read_to_string(&path).unwrap_or_else(|_| panic!("Missing conformance corpus {}", path.display()));
+    return serde_json::from_str(&text).unwrap_or_else(|error| panic!("Malformed {}: {}", path.display(), error));
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Converts a range of source code bytes to the corpus form, counting UTF-16 code units.
+fn source_pos_json(source_position: SourcePos, line_index: &LineIndex) -> Value {
+    return json!({
+        "startOffset": line_index.offset_to(source_position.start_offset, ColumnUnit::Utf16CodeUnits),
+        "endOffset": line_index.offset_to(source_position.end_offset, ColumnUnit::Utf16CodeUnits),
+    });
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Scans a source in the corpus form: its tokens through the first EOF (leaving out the extra EOF tokens that spare the
+// parser from checking for the end of the tokens) and its count of line feeds.
+fn scanned_json(source_code: &str) -> (Value, usize) {
+    let scan_result = scanner::scan(source_code);
+    let line_index = new_line_index("conformance", source_code, scan_result.new_line_offsets.new_ref());
+
+    let mut tokens: Vec<Value> = vec![];
+    for token in scan_result.tokens.iter() {
+        let source_pos = source_pos_json(new_source_pos(*token, &scan_result.long_token_lengths), &line_index);
+        let start_offset = source_pos["startOffset"].as_u64().unwrap();
+        let end_offset = source_pos["endOffset"].as_u64().unwrap();
+
+        tokens.push(json!({
+            "tokenType": token_types::tag_of_token_type(token.token_type),
+            "sourceOffset": start_offset,
+            "sourceLength": end_offset - start_offset,
+        }));
+
+        if token.token_type == token_types::EOF {
+            break;
+        }
+    }
+
+    return (Value::Array(tokens), scan_result.new_line_offsets.len());
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Converts an expression tree to the corpus form.
+fn tree_json(expr: &Expr, line_index: &LineIndex) -> Value {
+    let source_position = expr.get_source_position();
+    let text = source_position.get_text(line_index.source_code);
+
+    let mut result = json!({
+        "tag": expr.tag(),
+        "sourcePos": source_pos_json(source_position, line_index),
+    });
+
+    match expr {
+        Expr::BooleanLiteral { value, .. } => result["value"] = json!(value),
+        Expr::BuiltInType { .. } => result["name"] = json!(text),
+        Expr::Float64Literal { value, .. } => result["value"] = json!(value),
+        Expr::Identifier { .. } => result["name"] = json!(text),
+        Expr::Int64Literal { value, .. } => result["value"] = json!(value),
+        Expr::LeadingDocumentation { .. } => result["text"] = json!(text),
+        Expr::StringLiteral { value, .. } => result["value"] = json!(value),
+        Expr::TrailingDocumentation { .. } => result["text"] = json!(text),
+        _ => {}
+    }
+
+    let children = expr.children();
+    if !children.is_empty() {
+        result["children"] = Value::Array(
+            children.into_iter().map(|child| tree_json(child, line_index)).collect()
+        );
+    }
+
+    return result;
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Records the outcome of one case: a failure if the Rust output differs without a known divergence or matches
+// despite one, or else a known divergence if the Rust output differs as listed.
+fn check_case(
+    kind: &str,
+    name: &str,
+    divergences: &HashMap<String, String>,
+    expected: &Value,
+    actual: &Value,
+    outcomes: &mut Outcomes,
+) {
+    let describe = || format!("  expected: {}\n  actual:   {}", expected, actual);
+
+    match (expected == actual, divergences.get("rust")) {
+        (true, None) => {}
+        (true, Some(_)) => outcomes.failures.push(
+            format!("{} case '{}' no longer diverges; remove its \"rust\" divergence.", kind, name)
+        ),
+        (false, None) => outcomes.failures.push(
+            format!("{} case '{}' diverges.\n{}", kind, name, describe())
+        ),
+        (false, Some(reason)) => outcomes.known_divergences.push(
+            format!("{} case '{}' is a known divergence: {}\n{}", kind, name, reason, describe())
+        ),
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// The results of checking a file of cases.
+#[derive(Default)]
+struct Outcomes {
+    failures: Vec<String>,
+    known_divergences: Vec<String>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Checks the Rust scanner against every case of scanning.json.
+fn check_scanning_cases() -> Outcomes {
+    let mut outcomes = Outcomes::default();
+
+    for case in read_cases::<ScanningCase>("scanning.json") {
+        let (tokens, new_line_count) = scanned_json(&case.source);
+
+        check_case(
+            "Scanning",
+            &case.name,
+            &case.divergences,
+            &json!({ "tokens": case.tokens, "newLineCount": case.new_line_count }),
+            &json!({ "tokens": tokens, "newLineCount": new_line_count }),
+            &mut outcomes,
+        );
+    }
+
+    return outcomes;
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Checks the Rust parser against every case of parsing.json.
+fn check_parsing_cases() -> Outcomes {
+    let mut outcomes = Outcomes::default();
+
+    for case in read_cases::<ParsingCase>("parsing.json") {
+        let scan_result = leading_trailing_documentation::filter(scanner::scan(&case.source));
+        let parse_result = parser::parse_expression(&scan_result);
+        let line_index = new_line_index("conformance", &case.source, scan_result.new_line_offsets.new_ref());
+
+        let mut actual = tree_json(&parse_result.model(), &line_index);
+        if !parse_result.diagnostics.is_empty() {
+            actual["diagnostics"] = json!(parse_result.diagnostics.iter().map(|d| d.code.id()).collect::<Vec<_>>());
+        }
+
+        check_case("Parsing", &case.name, &case.divergences, &case.tree, &actual, &mut outcomes);
+    }
+
+    return outcomes;
+}
+
+//=====================================================================================================================
+
+#[test]
+fn test_scanning_conformance() {
+    let failures = check_scanning_cases().failures;
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn test_parsing_conformance() {
+    let failures = check_parsing_cases().failures;
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Fails while any known divergence remains, listing them all. It is ignored so that the known divergences show in the
+// summary of every test run without failing it; run it with --ignored to see them.
+#[test]
+#[ignore = "known divergences from the TypeScript front end (listed by cargo test -- --ignored)"]
+fn test_known_divergences() {
+    let mut known_divergences = check_scanning_cases().known_divergences;
+    known_divergences.extend(check_parsing_cases().known_divergences);
+
+    assert!(known_divergences.is_empty(), "{}", known_divergences.join("\n"));
+}
+
+//=====================================================================================================================
@@ -0,0 +1,842 @@
+//
+// # Canonical formatting of Lligne source code.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+// The formatter prints the parsed expression tree rather than adjusting the source text, so its output depends only
+// on the tree, the literal texts, and which items were separated by blank lines. Formatting formatted code therefore
+// changes nothing, and the output parses to the same tree as the input. Source code with syntax errors is left alone.
+//
+// The layout rules:
+//
+//   - binary operators have one space on each side except '.', '..', and ':' (which has one space after);
+//   - a record, array, or argument list stays on one line if it fits within the line width and holds no
+//     documentation or string blocks; otherwise each item goes on its own line, indented, with a trailing comma;
+//   - the items of a source file each go on their own line;
+//   - a single blank line between items is kept;
+//   - leading documentation goes on its own lines before the documented expression;
+//   - trailing documentation ends its line, aligned with the trailing documentation of adjacent lines;
+//   - string blocks keep one line per back tick, aligned under the first;
+//   - chains of operators are not broken, even when too long for the line.
+//
+
+use shared_vector::SharedVector;
+use crate::lligne::code::parsing::expressions::StringDelimiters;
+use crate::lligne::code::parsing::parser;
+use crate::lligne::code::parsing::syntax_tree::{NodeId, NodeKind, SyntaxTree};
+use crate::lligne::code::scanning::scanner;
+use crate::lligne::code::scanning::token_filters::leading_trailing_documentation;
+use crate::lligne::code::util::diagnostics::Diagnostic;
+
+//=====================================================================================================================
+
+// Settings for the layout of formatted code.
+#[derive(Debug, Copy, Clone)]
+pub struct FormatOptions {
+    pub line_width: usize,
+    pub indent_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        return FormatOptions {
+            line_width: 100,
+            indent_width: 4,
+        };
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+pub struct Outcome<'a> {
+    pub source_code: &'a str,
+    pub new_line_offsets: SharedVector<u32>,
+    pub formatted: Option<String>,
+    pub diagnostics: SharedVector<Diagnostic>,
+}
+
+//=====================================================================================================================
+
+// Formats a whole source file. The formatted code is None if the source code has errors, which are then given in the
+// diagnostics.
+pub fn format_source_file<'a>(source_code: &'a str, options: &FormatOptions) -> Outcome<'a> {
+    let scan_result = leading_trailing_documentation::filter(scanner::scan(source_code));
+    let parse_result = parser::parse_source_file(&scan_result);
+
+    let formatted = match parse_result.diagnostics.is_empty() {
+        true => Some(format_syntax_tree(&parse_result.syntax_tree, source_code, options)),
+        false => None,
+    };
+
+    return Outcome {
+        source_code,
+        new_line_offsets: scan_result.new_line_offsets.new_ref(),
+        formatted,
+        diagnostics: parse_result.diagnostics.new_ref(),
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Formats a syntax tree (normally of a source file) parsed from the given source code.
+pub fn format_syntax_tree(syntax_tree: &SyntaxTree, source_code: &str, options: &FormatOptions) -> String {
+    let mut printer = new_printer(syntax_tree, source_code, options);
+    printer.write_expr(syntax_tree.root());
+    return printer.render();
+}
+
+//=====================================================================================================================
+
+// One line of output: its indentation, text, and trailing documentation. A closed line ended with a string block and
+// can take no more text.
+struct Line {
+    indent: usize,
+    text: String,
+    comment: Option<String>,
+    closed: bool,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Lays out an expression tree as lines of text.
+struct Printer<'a> {
+    syntax_tree: &'a SyntaxTree,
+    source_code: &'a str,
+    options: FormatOptions,
+    indent: usize,
+    lines: Vec<Line>,
+    comment_line: usize,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+fn new_printer<'a>(syntax_tree: &'a SyntaxTree, source_code: &'a str, options: &FormatOptions) -> Printer<'a> {
+    return Printer {
+        syntax_tree,
+        source_code,
+        options: *options,
+        indent: 0,
+        lines: vec![new_line(0)],
+        comment_line: 0,
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+fn new_line(indent: usize) -> Line {
+    return Line {
+        indent,
+        text: String::new(),
+        comment: None,
+        closed: false,
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl<'a> Printer<'a> {
+    // Prints one expression (recursively).
+    fn write_expr(&mut self, node_id: NodeId) {
+        let children: Vec<NodeId> = self.syntax_tree.children(node_id).collect();
+        let syntax_tree = self.syntax_tree;
+        let source_code = self.source_code;
+        let text_of = |node_id: NodeId| syntax_tree.source_position(node_id).get_text(source_code);
+
+        if let Some(operator) = infix_operator(syntax_tree.kind(node_id)) {
+            self.write_expr(children[0]);
+            match operator {
+                "." => self.write_dot(children[0]),
+                ".." => self.write(operator),
+                ":" => self.write_colon(),
+                _ => self.write_operator(operator),
+            }
+            self.write_expr(children[1]);
+            return;
+        }
+
+        match syntax_tree.kind(node_id) {
+            NodeKind::ArrayLiteral =>
+                self.write_list("[", "]", &children, false),
+
+            NodeKind::Document => {
+                match (syntax_tree.kind(children[0]), syntax_tree.kind(children[1])) {
+                    (NodeKind::LeadingDocumentation, _) => {
+                        self.write_leading_documentation(text_of(children[0]));
+                        self.write_expr(children[1]);
+                    }
+                    (_, NodeKind::TrailingDocumentation) => {
+                        self.write_expr(children[0]);
+                        self.write_trailing_documentation(text_of(children[1]));
+                    }
+                    _ => {
+                        self.write_expr(children[0]);
+                        self.write_expr(children[1]);
+                    }
+                }
+            }
+
+            NodeKind::FunctionArguments =>
+                self.write_list("(", ")", &children, children.len() == 1),
+
+            NodeKind::FunctionCall => {
+                self.write_expr(children[0]);
+                match syntax_tree.kind(children[1]) {
+                    NodeKind::FunctionArguments => {
+                        let items: Vec<NodeId> = syntax_tree.children(children[1]).collect();
+                        self.write_list("(", ")", &items, false);
+                    }
+                    _ => self.write_expr(children[1]),
+                }
+            }
+
+            NodeKind::Index => {
+                self.write_expr(children[0]);
+                self.write("[");
+                self.write_expr(children[1]);
+                self.write("]");
+            }
+
+            NodeKind::LeadingDocumentation =>
+                self.write_leading_documentation(text_of(node_id)),
+
+            NodeKind::LogicalNotOperation => {
+                self.write("not ");
+                self.write_expr(children[0]);
+            }
+
+            NodeKind::NegationOperation => {
+                // Keep "- 1" apart so that it does not become the negative literal "-1".
+                match syntax_tree.kind(children[0]) {
+                    NodeKind::Int64Literal(_) => self.write("- "),
+                    _ => self.write("-"),
+                }
+                self.write_expr(children[0]);
+            }
+
+            NodeKind::Optional => {
+                self.write_expr(children[0]);
+                self.write("?");
+            }
+
+            NodeKind::Parenthesized => {
+                self.write("(");
+                self.write_expr(children[0]);
+                self.write(")");
+            }
+
+            NodeKind::Record =>
+                self.write_list("{", "}", &children, false),
+
+            NodeKind::Slice => {
+                self.write_expr(children[0]);
+                self.write("[");
+                self.write_expr(children[1]);
+                self.write("..");
+                self.write_expr(children[2]);
+                self.write("]");
+            }
+
+            NodeKind::SourceFile =>
+                self.write_source_file_items(&children),
+
+            NodeKind::StringLiteral(StringDelimiters::BackTicksMultiline, _) |
+            NodeKind::StringLiteral(StringDelimiters::DoubleQuotesMultiline, _) |
+            NodeKind::StringLiteral(StringDelimiters::SingleQuotesMultiline, _) =>
+                self.write_string_block(text_of(node_id)),
+
+            NodeKind::TrailingDocumentation =>
+                self.write_trailing_documentation(text_of(node_id)),
+
+            NodeKind::Unit =>
+                self.write("()"),
+
+            _ =>
+                self.write(text_of(node_id)),
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Prints the items of a record, array, or argument list, all on one line if they fit or else one per line. A lone
+    // item may need a comma to keep a parenthesized argument list from reading as a parenthesized expression.
+    fn write_list(&mut self, opening: &str, closing: &str, items: &[NodeId], single_needs_comma: bool) {
+        if let Some(flat) = self.flat_list(opening, closing, items, single_needs_comma) {
+            self.ensure_open();
+            if self.column() + flat.chars().count() <= self.options.line_width {
+                self.write(&flat);
+                return;
+            }
+        }
+
+        self.write(opening);
+        self.indent += self.options.indent_width;
+
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 && self.has_blank_line_between(items[index - 1], *item) {
+                self.write_blank_line();
+            }
+            self.start_line();
+            self.write_expr(*item);
+            self.write_separator();
+        }
+
+        self.indent -= self.options.indent_width;
+        self.start_line();
+        self.write(closing);
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Prints a list on one line (ignoring the line width), or returns None if it cannot be on one line.
+    fn flat_list(&self, opening: &str, closing: &str, items: &[NodeId], single_needs_comma: bool) -> Option<String> {
+        let options = FormatOptions {
+            line_width: usize::MAX,
+            ..self.options
+        };
+        let mut printer = new_printer(self.syntax_tree, self.source_code, &options);
+
+        printer.write(opening);
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                printer.write(", ");
+            }
+            printer.write_expr(*item);
+        }
+        if items.len() == 1 && single_needs_comma {
+            printer.write(",");
+        }
+        printer.write(closing);
+
+        return match printer.lines.as_slice() {
+            [line] if line.comment.is_none() && !line.closed => Some(line.text.clone()),
+            _ => None,
+        };
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Prints the items of a source file, one per line, separated by commas.
+    fn write_source_file_items(&mut self, items: &[NodeId]) {
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                self.write_separator();
+                if self.has_blank_line_between(items[index - 1], *item) {
+                    self.write_blank_line();
+                }
+            }
+            self.start_line();
+            self.write_expr(*item);
+        }
+
+        // Documentation trailing a final string block is on a line of its own and needs a comma to stay trailing.
+        if self.current().comment.is_some() && self.lines[self.comment_line].text.is_empty() {
+            self.write_separator();
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Determines whether the source code has a blank line between two adjacent items. The documentation or string
+    // block ending an item may have consumed the line feed ending its line.
+    fn has_blank_line_between(&self, previous: NodeId, next: NodeId) -> bool {
+        let previous_end = self.syntax_tree.source_position(previous).end_offset as usize;
+        let next_start = self.syntax_tree.source_position(next).start_offset as usize;
+
+        let ends_line = self.source_code[..previous_end].trim_end_matches([' ', '\t', '\r']).ends_with('\n');
+        let pieces: Vec<&str> = self.source_code[previous_end..next_start].split('\n').collect();
+        let whole_lines = pieces.get(if ends_line { 0 } else { 1 }..pieces.len() - 1).unwrap_or(&[]);
+
+        return whole_lines.iter().any(|line| line.trim().is_empty());
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Prints leading documentation on lines of its own; the documented expression follows on the next line.
+    fn write_leading_documentation(&mut self, text: &str) {
+        if !self.is_empty_line() {
+            let line = self.current();
+            line.text.truncate(line.text.trim_end().len());
+            self.lines.push(new_line(self.indent + self.options.indent_width));
+        }
+
+        // Keep the documentation from merging with documentation that ends the line before.
+        let line_count = self.lines.len();
+        if line_count > 1 {
+            let previous = &self.lines[line_count - 2];
+            if previous.comment.is_some() || previous.text.starts_with("//") {
+                self.lines.insert(line_count - 1, Line { closed: true, ..new_line(0) });
+            }
+        }
+
+        let indent = self.current().indent;
+        for documentation_line in documentation_lines(text) {
+            let line = self.current();
+            line.text += documentation_line;
+            line.closed = true;
+            self.lines.push(new_line(indent));
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Ends the current line with trailing documentation; further lines of it go on lines of their own. After a string
+    // block, the documentation starts a line of its own, since on the block's last line it would be part of the string.
+    fn write_trailing_documentation(&mut self, text: &str) {
+        if self.is_closed_by_string_block() {
+            self.lines.push(new_line(self.indent));
+        }
+
+        self.comment_line = self.lines.len() - 1;
+        let indent = self.current().indent;
+
+        for (index, documentation_line) in documentation_lines(text).enumerate() {
+            if index > 0 {
+                self.lines.push(new_line(indent));
+            }
+            self.current().comment = Some(documentation_line.to_string());
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Prints the lines of a string block, each without its indentation but keeping any trailing white space (part of
+    // the value), with the lines after the first aligned under it.
+    fn write_string_block(&mut self, text: &str) {
+        self.ensure_open();
+        let indent = self.column();
+
+        let block_lines = text.lines().map(|line| line.trim_start()).filter(|line| !line.is_empty());
+
+        for (index, block_line) in block_lines.enumerate() {
+            if index > 0 {
+                self.lines.push(new_line(indent));
+            }
+            let line = self.current();
+            line.text += block_line;
+            line.closed = true;
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Prints an infix operator with a space on each side (only after, at the start of a line).
+    fn write_operator(&mut self, operator: &str) {
+        if self.is_open() {
+            self.write(" ");
+        }
+        self.write(operator);
+        self.write(" ");
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Prints the '.' of a field reference, keeping it apart from a number ending the parent so the two do not read as
+    // a floating point literal.
+    fn write_dot(&mut self, parent: NodeId) {
+        if self.ends_with_number(parent) {
+            self.write_operator(".");
+        } else {
+            self.write(".");
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Determines whether the printed text of an expression ends with a numeric literal (possibly the last operand of
+    // an operation).
+    fn ends_with_number(&self, node_id: NodeId) -> bool {
+        let kind = self.syntax_tree.kind(node_id);
+
+        if infix_operator(kind).is_some() ||
+            matches!(kind, NodeKind::LogicalNotOperation | NodeKind::NegationOperation) {
+            return self.ends_with_number(self.syntax_tree.children(node_id).last().unwrap());
+        }
+
+        return matches!(kind, NodeKind::Float64Literal(_) | NodeKind::Int64Literal(_));
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Prints the ':' of a qualification, keeping it apart from a preceding '?' so the two do not read as '?:'.
+    fn write_colon(&mut self) {
+        if self.is_open() && self.current().text.ends_with('?') {
+            self.write(" ");
+        }
+        self.write(": ");
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Prints the comma after an item: before any trailing documentation of the item, or on a line of its own after a
+    // string block.
+    fn write_separator(&mut self) {
+        if self.current().comment.is_some() {
+            self.lines[self.comment_line].text += ",";
+        } else {
+            if self.current().closed {
+                self.lines.push(new_line(self.indent));
+            }
+            self.write(",");
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Appends text to the current line, first starting a continuation line if the current line has ended.
+    fn write(&mut self, text: &str) {
+        self.ensure_open();
+        self.current().text += text;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Starts a continuation line if the current line has ended with documentation or a string block.
+    fn ensure_open(&mut self) {
+        if !self.is_open() {
+            let indent = match self.is_closed_empty_line() {
+                true => self.indent,
+                false => self.indent + self.options.indent_width,
+            };
+            self.lines.push(new_line(indent));
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Starts a line at the current indentation unless the current line is still empty.
+    fn start_line(&mut self) {
+        if self.is_empty_line() {
+            self.current().indent = self.indent;
+        } else {
+            self.lines.push(new_line(self.indent));
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Adds a blank line.
+    fn write_blank_line(&mut self) {
+        if !self.is_empty_line() {
+            self.lines.push(new_line(0));
+        }
+        self.current().closed = true;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    fn current(&mut self) -> &mut Line {
+        return self.lines.last_mut().unwrap();
+    }
+
+    fn column(&self) -> usize {
+        let line = self.lines.last().unwrap();
+        return line.indent + line.text.chars().count();
+    }
+
+    fn is_open(&self) -> bool {
+        let line = self.lines.last().unwrap();
+        return line.comment.is_none() && !line.closed;
+    }
+
+    fn is_empty_line(&self) -> bool {
+        return self.is_open() && self.lines.last().unwrap().text.is_empty();
+    }
+
+    fn is_closed_empty_line(&self) -> bool {
+        let line = self.lines.last().unwrap();
+        return line.closed && line.text.is_empty();
+    }
+
+    fn is_closed_by_string_block(&self) -> bool {
+        let line = self.lines.last().unwrap();
+        return line.closed && line.comment.is_none() && !line.text.is_empty() && !line.text.starts_with("//");
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Joins the lines, aligning the trailing documentation of each run of adjacent lines that have it.
+    fn render(self) -> String {
+        let lines = &self.lines;
+        let line_count = lines.iter()
+            .rposition(|line| !line.text.is_empty() || line.comment.is_some())
+            .map_or(0, |last| last + 1);
+
+        let width = |line: &Line| if line.text.is_empty() { 0 } else { line.indent + line.text.chars().count() };
+
+        let mut result = String::new();
+        let mut index = 0;
+
+        while index < line_count {
+            let run_end = (index..line_count).find(|&i| lines[i].comment.is_none()).unwrap_or(line_count);
+            let comment_column = lines[index..run_end].iter().map(width).max().unwrap_or(0) + 2;
+
+            for line in &lines[index..run_end.max(index + 1)] {
+                if !line.text.is_empty() {
+                    result += &" ".repeat(line.indent);
+                    result += &line.text;
+                }
+                if let Some(comment) = &line.comment {
+                    let padding = match line.text.is_empty() {
+                        true => comment_column,
+                        false => comment_column - width(line),
+                    };
+                    result += &" ".repeat(padding);
+                    result += comment;
+                }
+                result += "\n";
+            }
+
+            index = run_end.max(index + 1);
+        }
+
+        return result;
+    }
+}
+
+//=====================================================================================================================
+
+// Returns the operator of an infix operation, or None for any other kind of node.
+fn infix_operator(kind: &NodeKind) -> Option<&'static str> {
+    return match kind {
+        NodeKind::Addition => Some("+"),
+        NodeKind::As => Some("as"),
+        NodeKind::Division => Some("/"),
+        NodeKind::Equals => Some("=="),
+        NodeKind::FieldReference => Some("."),
+        NodeKind::FunctionArrow => Some("->"),
+        NodeKind::GreaterThan => Some(">"),
+        NodeKind::GreaterThanOrEquals => Some(">="),
+        NodeKind::In => Some("in"),
+        NodeKind::Intersect => Some("&"),
+        NodeKind::IntersectAssignValue => Some("="),
+        NodeKind::IntersectDefaultValue => Some("?:"),
+        NodeKind::IntersectLowPrecedence => Some("&&"),
+        NodeKind::Is => Some("is"),
+        NodeKind::LessThan => Some("<"),
+        NodeKind::LessThanOrEquals => Some("<="),
+        NodeKind::LogicalAnd => Some("and"),
+        NodeKind::LogicalOr => Some("or"),
+        NodeKind::Match => Some("=~"),
+        NodeKind::Multiplication => Some("*"),
+        NodeKind::NotEquals => Some("!="),
+        NodeKind::NotMatch => Some("!~"),
+        NodeKind::Qualify => Some(":"),
+        NodeKind::Range => Some(".."),
+        NodeKind::Subtraction => Some("-"),
+        NodeKind::Union => Some("|"),
+        NodeKind::When => Some("when"),
+        NodeKind::Where => Some("where"),
+        _ => None,
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Splits the text of a documentation token into its trimmed "//" lines.
+fn documentation_lines(text: &str) -> impl Iterator<Item=&str> {
+    return text.lines().map(|line| line.trim()).filter(|line| !line.is_empty());
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+    use crate::lligne::code::parsing::expressions::Expr;
+    use crate::lligne::code::parsing::s_expressions::to_compact_s_expression;
+    use crate::lligne::code::parsing::visitor::preorder;
+    use super::*;
+
+    // Describes the parsed tree of some source code, ignoring the layout of documentation.
+    fn tree_shape(source_code: &str) -> Vec<String> {
+        let scan_result = leading_trailing_documentation::filter(scanner::scan(source_code));
+        let parse_result = parser::parse_source_file(&scan_result);
+        assert!(parse_result.diagnostics.is_empty(), "Formatted code has errors:\n{}", source_code);
+
+        return preorder(&parse_result.model()).map(|expr| match expr {
+            Expr::LeadingDocumentation { source_position } | Expr::TrailingDocumentation { source_position } => {
+                let lines: Vec<&str> = documentation_lines(source_position.get_text(source_code)).collect();
+                format!("{} {:?}", expr.tag(), lines)
+            }
+            _ if expr.children().is_empty() => to_compact_s_expression(expr, source_code),
+            _ => expr.tag().to_string(),
+        }).collect();
+    }
+
+    // Formats some source code, checking that the tree is unchanged and that formatting again changes nothing.
+    fn check_properties(source_code: &str, options: &FormatOptions) -> String {
+        let formatted = format_source_file(source_code, options).formatted.unwrap();
+
+        assert_eq!(tree_shape(source_code), tree_shape(&formatted), "Tree changed by formatting:\n{}", formatted);
+        assert_eq!(
+            formatted,
+            format_source_file(&formatted, options).formatted.unwrap(),
+            "Formatting is not idempotent:\n{}",
+            formatted
+        );
+
+        return formatted;
+    }
+
+    fn check(source_code: &str, expected: &str) {
+        assert_eq!(expected, check_properties(source_code, &FormatOptions::default()));
+    }
+
+    #[test]
+    fn test_operators() {
+        check("a+b*-c", "a + b * -c\n");
+        check("x  in 1..9 and not(y is Widget)", "x in 1..9 and not (y is Widget)\n");
+        check("f:(n:Int64)->Int64", "f: (n: Int64) -> Int64\n");
+        check("xs[ 1 .. 3 ].name [0]", "xs[1..3].name[0]\n");
+        check("a?:b, c? : d", "a ?: b,\nc? : d\n");
+        check("s=~t or s!~u", "s =~ t or s !~ u\n");
+        check("-1 * - 2 - -9223372036854775808", "-1 * - 2 - -9223372036854775808\n");
+        check("a: 1 . 2, b: x.2, c: 1..2", "a: 1 . 2,\nb: x.2,\nc: 1..2\n");
+        check("p = v2.x", "p = v2.x\n");
+        check("q = (1).x, r = 2.5 . y", "q = (1).x,\nr = 2.5 . y\n");
+        check("s = x.2 . 3, t = -1 . z", "s = x.2 . 3,\nt = -1 . z\n");
+    }
+
+    #[test]
+    fn test_records() {
+        check("p:{x:1,y:2}", "p: {x: 1, y: 2}\n");
+        check("e = {}, u = (), one = (x,), call = f(x)", "e = {},\nu = (),\none = (x,),\ncall = f(x)\n");
+        assert_eq!(
+            "point: {\n  x: Int64 = 0,\n  y: Int64 = 0,\n}\n",
+            check_properties("point: {x: Int64 = 0, y: Int64 = 0}", &FormatOptions { line_width: 30, indent_width: 2 })
+        );
+    }
+
+    #[test]
+    fn test_documentation() {
+        check(
+            "// A point.\npoint: {\n  x: 1, // across\n  yy: 2,  // down\n  // up\n}",
+            "// A point.\npoint: {\n    x: 1,   // across\n    yy: 2,  // down\n            // up\n}\n",
+        );
+        check("a: 1,\n\n\n// b\n  b: 2", "a: 1,\n\n// b\nb: 2\n");
+        check("a: 1, // one\n// two\nb: 2", "a: 1,  // one\n       // two\nb: 2\n");
+        check("a // x\n + b", "a  // x\n    + b\n");
+    }
+
+    #[test]
+    fn test_string_blocks() {
+        check("s = `one\n      `two\n, t = 'u'", "s = `one\n    `two\n,\nt = 'u'\n");
+        check("r = {s: `a  \n`b\n}", "r = {\n    s: `a  \n       `b\n    ,\n}\n");
+        check("a: `b\n, // d\nc: 1", "a: `b\n,  // d\nc: 1\n");
+        check("[1, 2],\n\n`b\n, // d\n", "[1, 2],\n\n`b\n,  // d\n");
+    }
+
+    // Generates source code from a fixed seed: items built from atoms, operators, lists, string blocks, and
+    // documentation, with irregular spacing.
+    struct Generator {
+        state: u64,
+    }
+
+    impl Generator {
+        fn next(&mut self, bound: usize) -> usize {
+            self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            return (self.state >> 33) as usize % bound;
+        }
+
+        fn pick<'s>(&mut self, choices: &[&'s str]) -> &'s str {
+            return choices[self.next(choices.len())];
+        }
+
+        fn expr(&mut self, depth: usize) -> String {
+            let choice = if depth == 0 { 0 } else { self.next(8) };
+
+            return match choice {
+                0 | 1 => self.pick(&["a", "b2", "1", "20", "2.5", "'s'", "\"t\"", "true", "Int64", "()", "`x \n"])
+                    .to_string(),
+                2 | 3 => {
+                    let operator = self.pick(&[
+                        "+", "-", "*", "/", ".", "..", ":", "=", "?:", "&", "&&", "|", "->", "and", "or", "in", "is",
+                        "==", "<", ">=", "=~", "when", "where",
+                    ]);
+                    let space = self.pick(&["", " ", "  ", "\n"]);
+                    let (lhs, rhs) = (self.expr(depth - 1), self.expr(depth - 1));
+                    format!("{}{}{} {}", lhs, space, operator, rhs)
+                }
+                4 => format!("{}{}", self.pick(&["-", "not ", "- "]), self.expr(depth - 1)),
+                5 => format!("({}){}", self.expr(depth - 1), self.pick(&["", "?", "[0]", ".c", "(x)"])),
+                _ => {
+                    let (opening, closing) = match self.next(3) {
+                        0 => ("{", "}"),
+                        1 => ("[", "]"),
+                        _ => ("f(", ")"),
+                    };
+                    format!("{}{}{}", opening, self.items(depth - 1), closing)
+                }
+            };
+        }
+
+        fn items(&mut self, depth: usize) -> String {
+            let mut result = String::new();
+            for _ in 0..self.next(4) {
+                result += self.pick(&["", "", "\n// lead\n", "\n\n"]);
+                result += &self.expr(depth);
+                result += self.pick(&[",", ",", ", ", ",\n", ", // trail\n", " // trail\n,"]);
+            }
+            return result;
+        }
+    }
+
+    #[test]
+    fn test_generated_inputs() {
+        let mut generator = Generator { state: 1 };
+        let mut checked = 0;
+
+        for _ in 0..2000 {
+            let source_code = generator.items(3);
+            let options = FormatOptions { line_width: [100, 30, 10][generator.next(3)], indent_width: 4 };
+
+            let outcome = format_source_file(&source_code, &options);
+            if outcome.diagnostics.is_empty() {
+                check_properties(&source_code, &options);
+                checked += 1;
+            }
+        }
+
+        assert!(checked > 1000, "Too few generated inputs parse: {}", checked);
+    }
+
+    #[test]
+    fn test_line_width() {
+        let source_code = "f(alpha, beta, gamma)";
+
+        assert_eq!("f(alpha, beta, gamma)\n", check_properties(source_code, &FormatOptions::default()));
+        assert_eq!(
+            "f(\n  alpha,\n  beta,\n  gamma,\n)\n",
+            check_properties(source_code, &FormatOptions { line_width: 20, indent_width: 2 })
+        );
+    }
+
+    #[test]
+    fn test_errors_left_alone() {
+        let outcome = format_source_file("a: {1, ", &FormatOptions::default());
+
+        assert_eq!(None, outcome.formatted);
+        assert!(!outcome.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures");
+
+        for entry in fs::read_dir(fixtures_dir).unwrap() {
+            let path = entry.unwrap().path();
+            let source_code = fs::read_to_string(&path).unwrap();
+
+            if path.extension().is_some_and(|extension| extension == "llg") &&
+                format_source_file(&source_code, &FormatOptions::default()).diagnostics.is_empty() {
+                for line_width in [100, 40, 10] {
+                    check_properties(&source_code, &FormatOptions { line_width, indent_width: 4 });
+                }
+            }
+        }
+    }
+}
+
+//=====================================================================================================================
@@ -0,0 +1,299 @@
+//
+// # Formatting as minimal text edits: whole files, selected ranges, and on-type formatting.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+// Rather than replacing the document, the editor applies a few small edits so that the cursor stays put and code
+// outside the formatted range is untouched. The edits come from lining up the tokens of the source code with those of
+// its formatted code: the formatter keeps every token other than the ',' separators, so the edits are the differences
+// in the text between matching tokens (whitespace and separators) and within documentation and string blocks (their
+// indentation), each trimmed to the characters that actually change.
+//
+
+use serde::Serialize;
+use shared_vector::SharedVector;
+use crate::lligne::code::formatting::formatter::{format_syntax_tree, FormatOptions};
+use crate::lligne::code::parsing::parser;
+use crate::lligne::code::parsing::syntax_tree::{NodeId, NodeKind, SyntaxTree};
+use crate::lligne::code::scanning::{scanner, token_types};
+use crate::lligne::code::scanning::token_filters::leading_trailing_documentation;
+use crate::lligne::code::util::diagnostics::Diagnostic;
+use crate::lligne::code::util::source_pos::{new_source_pos, SourcePos};
+
+//=====================================================================================================================
+
+// Replaces the source code at source_position with new_text. In JSON an edit is
+// {"sourcePosition": {"startOffset": ..., "endOffset": ...}, "newText": ...}.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextEdit {
+    pub source_position: SourcePos,
+    pub new_text: String,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// The edits for formatting (in source order, not overlapping), or none if the source code has errors, which are then
+// given in the diagnostics.
+pub struct Outcome<'a> {
+    pub source_code: &'a str,
+    pub new_line_offsets: SharedVector<u32>,
+    pub edits: Vec<TextEdit>,
+    pub diagnostics: SharedVector<Diagnostic>,
+}
+
+//=====================================================================================================================
+
+// Computes the edits that format a whole source file.
+pub fn format_edits<'a>(source_code: &'a str, options: &FormatOptions) -> Outcome<'a> {
+    return format_parsed(source_code, options, |_| Some(SourcePos { start_offset: 0, end_offset: u32::MAX }));
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Computes the edits that format the given range of a source file: those lying within the range.
+pub fn format_range<'a>(source_code: &'a str, range: SourcePos, options: &FormatOptions) -> Outcome<'a> {
+    return format_parsed(source_code, options, |_| Some(range));
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Computes the edits for formatting after the user types '}' or ',' at the given offset: those within the innermost
+// record, array, or argument list around the typed character, or else within the enclosing top-level item. Other
+// characters need no edits.
+pub fn format_on_type<'a>(source_code: &'a str, offset: u32, typed: char, options: &FormatOptions) -> Outcome<'a> {
+    if typed != '}' && typed != ',' {
+        return format_parsed(source_code, options, |_| None);
+    }
+
+    return format_parsed(source_code, options, |syntax_tree| {
+        let node_id = syntax_tree.node_at_offset(offset)?;
+
+        let is_list_around = |node_id: NodeId| {
+            let source_position = syntax_tree.source_position(node_id);
+            let is_list = matches!(
+                syntax_tree.kind(node_id),
+                NodeKind::ArrayLiteral | NodeKind::FunctionArguments | NodeKind::Record
+            );
+            return is_list && source_position.start_offset < offset && offset < source_position.end_offset;
+        };
+        let is_item = |node_id: NodeId| syntax_tree.parent(node_id) == Some(syntax_tree.root());
+
+        return std::iter::once(node_id)
+            .chain(syntax_tree.ancestors(node_id))
+            .find(|&ancestor| is_list_around(ancestor) || is_item(ancestor))
+            .map(|ancestor| syntax_tree.source_position(ancestor));
+    });
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Parses a source file, chooses the range to format from the parsed tree, and computes the edits within that range.
+fn format_parsed<'a>(
+    source_code: &'a str,
+    options: &FormatOptions,
+    choose_range: impl FnOnce(&SyntaxTree) -> Option<SourcePos>,
+) -> Outcome<'a> {
+    let scan_result = leading_trailing_documentation::filter(scanner::scan(source_code));
+    let parse_result = parser::parse_source_file(&scan_result);
+
+    let mut edits = vec![];
+
+    if parse_result.diagnostics.is_empty() {
+        if let Some(range) = choose_range(&parse_result.syntax_tree) {
+            let formatted = format_syntax_tree(&parse_result.syntax_tree, source_code, options);
+
+            edits = text_edits(source_code, &formatted).into_iter()
+                .filter(|edit| is_within(edit.source_position, range))
+                .collect();
+        }
+    }
+
+    return Outcome {
+        source_code,
+        new_line_offsets: scan_result.new_line_offsets.new_ref(),
+        edits,
+        diagnostics: parse_result.diagnostics.new_ref(),
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Determines whether an edit lies within a range. An insertion at either end belongs to the code outside the range.
+fn is_within(edit_position: SourcePos, range: SourcePos) -> bool {
+    if edit_position.start_offset == edit_position.end_offset {
+        return range.start_offset < edit_position.start_offset && edit_position.end_offset < range.end_offset;
+    }
+
+    return range.start_offset <= edit_position.start_offset && edit_position.end_offset <= range.end_offset;
+}
+
+//=====================================================================================================================
+
+// Computes minimal edits changing old_code to new_code, which must have the same tokens apart from separators.
+fn text_edits(old_code: &str, new_code: &str) -> Vec<TextEdit> {
+    let old_tokens = anchor_tokens(old_code);
+    let new_tokens = anchor_tokens(new_code);
+
+    let mut result = vec![];
+
+    let same_tokens = old_tokens.len() == new_tokens.len() &&
+        old_tokens.iter().zip(&new_tokens).all(|((old_type, _), (new_type, _))| old_type == new_type);
+
+    if !same_tokens {
+        push_edit(old_code, 0..old_code.len(), new_code, &mut result);
+        return result;
+    }
+
+    let mut old_end = 0;
+    let mut new_end = 0;
+
+    for ((_, old_position), (_, new_position)) in old_tokens.iter().zip(&new_tokens) {
+        let old_start = old_position.start_offset as usize;
+        let new_start = new_position.start_offset as usize;
+
+        push_edit(old_code, old_end..old_start, &new_code[new_end..new_start], &mut result);
+
+        old_end = old_position.end_offset as usize;
+        new_end = new_position.end_offset as usize;
+
+        push_edit(old_code, old_start..old_end, &new_code[new_start..new_end], &mut result);
+    }
+
+    push_edit(old_code, old_end..old_code.len(), &new_code[new_end..], &mut result);
+
+    return result;
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Lists the types and positions of the tokens of some code other than separators and the end of file.
+fn anchor_tokens(code: &str) -> Vec<(u16, SourcePos)> {
+    let scan_result = scanner::scan(code);
+
+    return scan_result.tokens.iter()
+        .filter(|token| !matches!(token.token_type, token_types::COMMA | token_types::EOF))
+        .map(|token| (token.token_type, new_source_pos(*token, &scan_result.long_token_lengths)))
+        .collect();
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Adds an edit replacing the given range of old code by new text unless they are the same, leaving out any common
+// prefix and suffix.
+fn push_edit(old_code: &str, range: std::ops::Range<usize>, new_text: &str, edits: &mut Vec<TextEdit>) {
+    let old_text = &old_code[range.clone()];
+    if old_text == new_text {
+        return;
+    }
+
+    let prefix_length: usize = old_text.chars().zip(new_text.chars())
+        .take_while(|(old_char, new_char)| old_char == new_char)
+        .map(|(old_char, _)| old_char.len_utf8())
+        .sum();
+    let suffix_length: usize = old_text[prefix_length..].chars().rev().zip(new_text[prefix_length..].chars().rev())
+        .take_while(|(old_char, new_char)| old_char == new_char)
+        .map(|(old_char, _)| old_char.len_utf8())
+        .sum();
+
+    edits.push(TextEdit {
+        source_position: SourcePos {
+            start_offset: (range.start + prefix_length) as u32,
+            end_offset: (range.end - suffix_length) as u32,
+        },
+        new_text: new_text[prefix_length..new_text.len() - suffix_length].to_string(),
+    });
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::lligne::code::formatting::formatter::format_source_file;
+    use super::*;
+
+    fn apply_edits(source_code: &str, edits: &[TextEdit]) -> String {
+        let mut result = source_code.to_string();
+
+        for edit in edits.iter().rev() {
+            let range = edit.source_position.start_offset as usize..edit.source_position.end_offset as usize;
+            result.replace_range(range, &edit.new_text);
+        }
+
+        return result;
+    }
+
+    fn edit(start_offset: u32, end_offset: u32, new_text: &str) -> TextEdit {
+        return TextEdit {
+            source_position: SourcePos { start_offset, end_offset },
+            new_text: new_text.to_string(),
+        };
+    }
+
+    #[test]
+    fn test_minimal_edits() {
+        let source_code = "x:1,  y: f (2)";
+        let outcome = format_edits(source_code, &FormatOptions::default());
+
+        assert_eq!(
+            vec![edit(2, 2, " "), edit(4, 6, "\n"), edit(10, 11, ""), edit(14, 14, "\n")],
+            outcome.edits
+        );
+        assert_eq!("x: 1,\ny: f(2)\n", apply_edits(source_code, &outcome.edits));
+    }
+
+    #[test]
+    fn test_edits_reach_formatted_code() {
+        let options = FormatOptions { line_width: 20, indent_width: 2 };
+        let sources = [
+            "// doc\n  a:{b:1,c:[1,2,3,4,5,6,7,8,9]} ,  b : 2 // two\n,c=`x\n     `y\n",
+            "f: (n: Int64) -> Int64 = 1 when n == 0\n    | n * f(n: n - 1) when n > 0,\nz = {a: 1, b: 2,}",
+            "",
+            "s = 'größe'  ,t = (1,2)",
+        ];
+
+        for source_code in sources {
+            let outcome = format_edits(source_code, &options);
+            let formatted = format_source_file(source_code, &options).formatted.unwrap();
+
+            assert_eq!(formatted, apply_edits(source_code, &outcome.edits));
+        }
+    }
+
+    #[test]
+    fn test_format_range() {
+        let source_code = "a = 1+2,\nb = 3+4,\nc = 5+6";
+        let range = SourcePos { start_offset: 9, end_offset: 16 };
+        let outcome = format_range(source_code, range, &FormatOptions::default());
+
+        assert_eq!("a = 1+2,\nb = 3 + 4,\nc = 5+6", apply_edits(source_code, &outcome.edits));
+    }
+
+    #[test]
+    fn test_format_on_type() {
+        let options = FormatOptions::default();
+
+        let source_code = "a = 1+2,\nb = {x:1,y:  [3,4]}\n";
+        let outcome = format_on_type(source_code, 27, '}', &options);
+        assert_eq!("a = 1+2,\nb = {x: 1, y: [3, 4]}\n", apply_edits(source_code, &outcome.edits));
+
+        let outcome = format_on_type(source_code, 24, ',', &options);
+        assert_eq!("a = 1+2,\nb = {x:1,y:  [3, 4]}\n", apply_edits(source_code, &outcome.edits));
+
+        let outcome = format_on_type(source_code, 7, ',', &options);
+        assert_eq!("a = 1 + 2,\nb = {x:1,y:  [3,4]}\n", apply_edits(source_code, &outcome.edits));
+
+        assert!(format_on_type(source_code, 5, '+', &options).edits.is_empty());
+    }
+
+    #[test]
+    fn test_errors_give_no_edits() {
+        let outcome = format_range("a = (1+2", SourcePos { start_offset: 0, end_offset: 8 }, &FormatOptions::default());
+
+        assert!(outcome.edits.is_empty());
+        assert!(!outcome.diagnostics.is_empty());
+    }
+}
+
+//=====================================================================================================================
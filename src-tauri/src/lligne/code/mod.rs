@@ -1,4 +1,9 @@
-
+pub mod formatting;
 pub mod parsing;
 pub mod scanning;
 pub mod util;
+
+#[cfg(test)]
+mod conformance_tests;
+#[cfg(test)]
+mod snapshot_tests;
@@ -0,0 +1,333 @@
+//
+// # Lossless concrete syntax trees of Lligne source code.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+// A concrete syntax tree keeps every byte of the source code: each raw token from the scanner (documentation
+// included, before the leading/trailing documentation filter) carries the whitespace around it as trivia, and each
+// node of the expression tree owns the tokens between its children. Printing the tokens with their trivia in order
+// reproduces the source exactly. The parser's syntax tree is the typed view over the concrete tree: node n has the
+// kind syntax_tree().kind(n).
+//
+
+use crate::lligne::code::parsing::syntax_tree::{NodeId, SyntaxTree};
+use crate::lligne::code::scanning::{scanner, token_types};
+use crate::lligne::code::scanning::tokens::Token;
+use crate::lligne::code::util::source_pos::{new_source_pos, SourcePos};
+
+//=====================================================================================================================
+
+// A raw token with the whitespace around it. The trailing trivia runs from the end of the token through the end of
+// its line (or up to the next token if that is on the same line); the leading trivia is the rest of the whitespace
+// since the previous token.
+#[derive(Debug, Copy, Clone)]
+pub struct CstToken {
+    pub token: Token,
+    pub source_position: SourcePos,
+    pub leading_trivia: SourcePos,
+    pub trailing_trivia: SourcePos,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// One element of a concrete syntax tree node: a child node or one of the node's own tokens (an index into tokens()).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CstElement {
+    Node(NodeId),
+    Token(u32),
+}
+
+//=====================================================================================================================
+
+// A lossless concrete syntax tree over the raw tokens of some source code and the syntax tree parsed from them.
+pub struct ConcreteSyntaxTree<'a> {
+    source_code: &'a str,
+    syntax_tree: &'a SyntaxTree,
+    tokens: Vec<CstToken>,
+    node_elements: Vec<Vec<CstElement>>,
+    file_elements: Vec<CstElement>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Builds the concrete syntax tree for the unfiltered outcome of scanning some source code and the syntax tree parsed
+// from the same source code (after filtering).
+pub fn new_concrete_syntax_tree<'a>(
+    scan_result: &scanner::Outcome<'a>,
+    syntax_tree: &'a SyntaxTree,
+) -> ConcreteSyntaxTree<'a> {
+    let tokens = tokens_with_trivia(scan_result);
+    let root = syntax_tree.root();
+
+    let mut builder = Builder {
+        syntax_tree,
+        tokens: &tokens,
+        token_index: 0,
+        node_elements: vec![vec![]; syntax_tree.len()],
+    };
+
+    let mut file_elements = vec![];
+    builder.take_tokens_before(syntax_tree.source_position(root).start_offset, &mut file_elements);
+    file_elements.push(CstElement::Node(root));
+    builder.build_node(root);
+    builder.take_tokens_before(u32::MAX, &mut file_elements);
+    file_elements.push(CstElement::Token((tokens.len() - 1) as u32));
+
+    let node_elements = builder.node_elements;
+
+    return ConcreteSyntaxTree {
+        source_code: scan_result.source_code,
+        syntax_tree,
+        tokens,
+        node_elements,
+        file_elements,
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Attaches whitespace trivia to the raw tokens through the first EOF token, which takes any whitespace at the end.
+fn tokens_with_trivia(scan_result: &scanner::Outcome) -> Vec<CstToken> {
+    let source_code = scan_result.source_code;
+    let mut result: Vec<CstToken> = vec![];
+
+    for token in scan_result.tokens.iter() {
+        let source_position = new_source_pos(*token, &scan_result.long_token_lengths);
+        let gap_start = result.last().map_or(0, |previous| previous.source_position.end_offset);
+        let gap = &source_code[gap_start as usize..source_position.start_offset as usize];
+
+        let mut leading_start = gap_start;
+        if let Some(previous) = result.last_mut() {
+            leading_start += gap.find('\n').map_or(gap.len(), |index| index + 1) as u32;
+            previous.trailing_trivia = SourcePos { start_offset: gap_start, end_offset: leading_start };
+        }
+
+        result.push(CstToken {
+            token: *token,
+            source_position,
+            leading_trivia: SourcePos { start_offset: leading_start, end_offset: source_position.start_offset },
+            trailing_trivia: SourcePos {
+                start_offset: source_position.end_offset,
+                end_offset: source_position.end_offset,
+            },
+        });
+
+        if token.token_type == token_types::EOF {
+            break;
+        }
+    }
+
+    return result;
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Distributes the tokens (all but the final EOF) among the nodes in source order.
+struct Builder<'b> {
+    syntax_tree: &'b SyntaxTree,
+    tokens: &'b [CstToken],
+    token_index: usize,
+    node_elements: Vec<Vec<CstElement>>,
+}
+
+impl<'b> Builder<'b> {
+    // Fills in the elements of one node and (recursively) its children.
+    fn build_node(&mut self, node_id: NodeId) {
+        let mut children: Vec<NodeId> = self.syntax_tree.children(node_id).collect();
+        children.sort_by_key(|&child| self.syntax_tree.source_position(child).start_offset);
+
+        let mut elements = vec![];
+
+        for child in children {
+            self.take_tokens_before(self.syntax_tree.source_position(child).start_offset, &mut elements);
+            elements.push(CstElement::Node(child));
+            self.build_node(child);
+        }
+
+        self.take_tokens_before(self.syntax_tree.source_position(node_id).end_offset, &mut elements);
+
+        self.node_elements[node_id.0 as usize] = elements;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Moves the not yet placed tokens starting before the given offset into the given elements.
+    fn take_tokens_before(&mut self, offset: u32, elements: &mut Vec<CstElement>) {
+        while self.token_index < self.tokens.len() - 1 &&
+            self.tokens[self.token_index].source_position.start_offset < offset {
+            elements.push(CstElement::Token(self.token_index as u32));
+            self.token_index += 1;
+        }
+    }
+}
+
+//=====================================================================================================================
+
+impl<'a> ConcreteSyntaxTree<'a> {
+    // Returns the source code of the tree.
+    pub fn source_code(&self) -> &'a str {
+        return self.source_code;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the typed view of the tree: the parsed syntax tree with its node ids.
+    pub fn syntax_tree(&self) -> &'a SyntaxTree {
+        return self.syntax_tree;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns all the tokens in source order, ending with EOF.
+    pub fn tokens(&self) -> &[CstToken] {
+        return &self.tokens;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the top-level elements: any tokens before the root expression, the root, any tokens after it, and EOF.
+    pub fn file_elements(&self) -> &[CstElement] {
+        return &self.file_elements;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the child nodes and own tokens of a node in source order.
+    pub fn elements(&self, node_id: NodeId) -> &[CstElement] {
+        return &self.node_elements[node_id.0 as usize];
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the indexes of all the tokens within a node (its own and its descendants') in source order.
+    pub fn node_tokens(&self, node_id: NodeId) -> Vec<u32> {
+        let mut result = vec![];
+        self.collect_tokens(self.elements(node_id), &mut result);
+        return result;
+    }
+
+    fn collect_tokens(&self, elements: &[CstElement], result: &mut Vec<u32>) {
+        for element in elements {
+            match element {
+                CstElement::Node(child) => self.collect_tokens(self.elements(*child), result),
+                CstElement::Token(token_index) => result.push(*token_index),
+            }
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Reconstructs the text of a node: its tokens with the trivia between them, leaving out the leading trivia of its
+    // first token and the trailing trivia of its last.
+    pub fn node_text(&self, node_id: NodeId) -> String {
+        let token_indexes = self.node_tokens(node_id);
+        let mut result = String::new();
+
+        for (index, &token_index) in token_indexes.iter().enumerate() {
+            let token = &self.tokens[token_index as usize];
+            if index > 0 {
+                result += token.leading_trivia.get_text(self.source_code);
+            }
+            result += token.source_position.get_text(self.source_code);
+            if index < token_indexes.len() - 1 {
+                result += token.trailing_trivia.get_text(self.source_code);
+            }
+        }
+
+        return result;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Reconstructs the whole source code from the tokens and trivia of the tree.
+    pub fn to_source_code(&self) -> String {
+        let mut token_indexes = vec![];
+        self.collect_tokens(&self.file_elements, &mut token_indexes);
+
+        let mut result = String::new();
+        for token_index in token_indexes {
+            let token = &self.tokens[token_index as usize];
+            result += token.leading_trivia.get_text(self.source_code);
+            result += token.source_position.get_text(self.source_code);
+            result += token.trailing_trivia.get_text(self.source_code);
+        }
+
+        return result;
+    }
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+    use crate::lligne::code::parsing::parser;
+    use crate::lligne::code::parsing::syntax_tree::NodeKind;
+    use crate::lligne::code::scanning::token_filters::leading_trailing_documentation;
+    use super::*;
+
+    fn check_round_trip(source_code: &str) {
+        let scan_result = scanner::scan(source_code);
+        let filtered_scan_result = leading_trailing_documentation::filter(scanner::scan(source_code));
+        let parse_result = parser::parse_source_file(&filtered_scan_result);
+
+        let cst = new_concrete_syntax_tree(&scan_result, &parse_result.syntax_tree);
+
+        assert_eq!(source_code, cst.to_source_code());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        check_round_trip("");
+        check_round_trip("   \n\t ");
+        check_round_trip("x");
+        check_round_trip("  a  +\tb * (c-d)  \n");
+        check_round_trip("// doc\nx: Int64 = 1,   // trailing\r\ny: 'two'\n\n");
+        check_round_trip("s: ``` block\n   ``` lines\n t: 1");
+        check_round_trip("{a: 1, b: [2, 3],}\n\n// end\n");
+        check_round_trip("a + + ) ] ? $ ");
+        check_round_trip("f(x: 'größe') -> \"‽\"");
+    }
+
+    #[test]
+    fn test_round_trip_fixtures() {
+        let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures");
+
+        for entry in fs::read_dir(fixtures_dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|extension| extension == "llg") {
+                check_round_trip(&fs::read_to_string(&path).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_trivia_and_elements() {
+        let source_code = "  a +  b  \n";
+        let scan_result = scanner::scan(source_code);
+        let parse_result = parser::parse_expression(&scan_result);
+
+        let cst = new_concrete_syntax_tree(&scan_result, &parse_result.syntax_tree);
+        let trivia = |position: SourcePos| position.get_text(source_code);
+
+        let tokens = cst.tokens();
+        assert_eq!(4, tokens.len());
+        assert_eq!(("  ", " "), (trivia(tokens[0].leading_trivia), trivia(tokens[0].trailing_trivia)));
+        assert_eq!(("", "  "), (trivia(tokens[1].leading_trivia), trivia(tokens[1].trailing_trivia)));
+        assert_eq!(("", "  \n"), (trivia(tokens[2].leading_trivia), trivia(tokens[2].trailing_trivia)));
+        assert_eq!(token_types::EOF, tokens[3].token.token_type);
+
+        let root = cst.syntax_tree().root();
+        assert_eq!(
+            &[CstElement::Node(NodeId(0)), CstElement::Token(1), CstElement::Node(NodeId(1))],
+            cst.elements(root)
+        );
+        assert_eq!(&NodeKind::Addition, cst.syntax_tree().kind(root));
+        assert_eq!("a +  b", cst.node_text(root));
+        assert_eq!(vec![0, 1, 2], cst.node_tokens(root));
+    }
+}
+
+//=====================================================================================================================
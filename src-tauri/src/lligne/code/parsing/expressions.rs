@@ -6,10 +6,13 @@
 
 //=====================================================================================================================
 
+use serde::{Deserialize, Serialize};
 use shared_vector::SharedVector;
+use crate::lligne::code::scanning::string_literals::{decode_quoted_string, decode_string_block};
 use crate::lligne::code::util::source_pos::SourcePos;
 
 // TODO: six different string literal variants?
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StringDelimiters {
     SingleQuotes,
     DoubleQuotes,
@@ -19,16 +22,67 @@ pub enum StringDelimiters {
     BackTicksMultiline,
 }
 
+//---------------------------------------------------------------------------------------------------------------------
+
+impl StringDelimiters {
+    // Decodes the value of a string literal from its source text. Quoted strings have their escape sequences
+    // replaced; multiline forms are blocks of lines each opened by the delimiter, which is stripped along with the
+    // indentation before it. Back-ticked strings are raw text.
+    pub fn decode(&self, text: &str) -> String {
+        return match self {
+            StringDelimiters::SingleQuotes => decode_quoted_string(text),
+            StringDelimiters::DoubleQuotes => decode_quoted_string(text),
+            StringDelimiters::BackTicks => decode_string_block(text, '`', false),
+            StringDelimiters::SingleQuotesMultiline => decode_string_block(text, '\'', true),
+            StringDelimiters::DoubleQuotesMultiline => decode_string_block(text, '"', true),
+            StringDelimiters::BackTicksMultiline => decode_string_block(text, '`', false),
+        };
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the tag of a string literal with these delimiters, matching the TypeScript AST.
+    pub fn tag(&self) -> &'static str {
+        return match self {
+            StringDelimiters::SingleQuotes => "#SingleQuotedStringExpr",
+            StringDelimiters::DoubleQuotes => "#DoubleQuotedStringExpr",
+            StringDelimiters::BackTicks => "#BackTickedStringExpr",
+            StringDelimiters::SingleQuotesMultiline => "#SingleQuotedStringBlockExpr",
+            StringDelimiters::DoubleQuotesMultiline => "#DoubleQuotedStringBlockExpr",
+            StringDelimiters::BackTicksMultiline => "#BackTickedStringBlockExpr",
+        };
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the delimiters of a string literal with the given tag or None if the tag is not that of a string.
+    pub fn from_tag(tag: &str) -> Option<StringDelimiters> {
+        return match tag {
+            "#SingleQuotedStringExpr" => Some(StringDelimiters::SingleQuotes),
+            "#DoubleQuotedStringExpr" => Some(StringDelimiters::DoubleQuotes),
+            "#BackTickedStringExpr" => Some(StringDelimiters::BackTicks),
+            "#SingleQuotedStringBlockExpr" => Some(StringDelimiters::SingleQuotesMultiline),
+            "#DoubleQuotedStringBlockExpr" => Some(StringDelimiters::DoubleQuotesMultiline),
+            "#BackTickedStringBlockExpr" => Some(StringDelimiters::BackTicksMultiline),
+            _ => None,
+        };
+    }
+}
+
 //=====================================================================================================================
 
+// An expression node of the abstract syntax tree. See expressions_serde for its JSON form.
+#[derive(Clone, Debug)]
 pub enum Expr {
     Addition { source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr> },
     ArrayLiteral { source_position: SourcePos, elements: SharedVector<Box<Expr>> },
+    As { source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr> },
     BooleanLiteral { source_position: SourcePos, value: bool },
     BuiltInType { source_position: SourcePos },
     Division { source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr> },
     Document { source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr> },
     Equals { source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr> },
+    Error { source_position: SourcePos },
     FieldReference { source_position: SourcePos, parent: Box<Expr>, child: Box<Expr> },
     Float64Literal { source_position: SourcePos, value: f64 },
     FunctionArguments { source_position: SourcePos, items: SharedVector<Box<Expr>> },
@@ -38,6 +92,7 @@ pub enum Expr {
     GreaterThanOrEquals { source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr> },
     Identifier { source_position: SourcePos },
     In { source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr> },
+    Index { source_position: SourcePos, operand: Box<Expr>, index: Box<Expr> },
     Int64Literal { source_position: SourcePos, value: i64 },
     Intersect { source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr> },
     IntersectAssignValue { source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr> },
@@ -60,7 +115,9 @@ pub enum Expr {
     Qualify { source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr> },
     Range { source_position: SourcePos, first: Box<Expr>, last: Box<Expr> },
     Record { source_position: SourcePos, items: SharedVector<Box<Expr>> },
-    StringLiteral { source_position: SourcePos, delimiters: StringDelimiters },
+    Slice { source_position: SourcePos, operand: Box<Expr>, first: Box<Expr>, last: Box<Expr> },
+    SourceFile { source_position: SourcePos, items: SharedVector<Box<Expr>> },
+    StringLiteral { source_position: SourcePos, delimiters: StringDelimiters, value: String },
     Subtraction { source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr> },
     TrailingDocumentation { source_position: SourcePos },
     Union { source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr> },
@@ -77,11 +134,13 @@ impl Expr {
         return match self {
             Expr::Addition { source_position: result, .. } => result.clone(),
             Expr::ArrayLiteral { source_position: result, .. } => result.clone(),
+            Expr::As { source_position: result, .. } => result.clone(),
             Expr::BooleanLiteral { source_position: result, .. } => result.clone(),
             Expr::BuiltInType { source_position: result, .. } => result.clone(),
             Expr::Division { source_position: result, .. } => result.clone(),
             Expr::Document { source_position: result, .. } => result.clone(),
             Expr::Equals { source_position: result, .. } => result.clone(),
+            Expr::Error { source_position: result, .. } => result.clone(),
             Expr::FieldReference { source_position: result, .. } => result.clone(),
             Expr::Float64Literal { source_position: result, .. } => result.clone(),
             Expr::FunctionArguments { source_position: result, .. } => result.clone(),
@@ -91,6 +150,7 @@ impl Expr {
             Expr::GreaterThanOrEquals { source_position: result, .. } => result.clone(),
             Expr::Identifier { source_position: result, .. } => result.clone(),
             Expr::In { source_position: result, .. } => result.clone(),
+            Expr::Index { source_position: result, .. } => result.clone(),
             Expr::Int64Literal { source_position: result, .. } => result.clone(),
             Expr::Intersect { source_position: result, .. } => result.clone(),
             Expr::IntersectAssignValue { source_position: result, .. } => result.clone(),
@@ -113,6 +173,8 @@ impl Expr {
             Expr::Qualify { source_position: result, .. } => result.clone(),
             Expr::Range { source_position: result, .. } => result.clone(),
             Expr::Record { source_position: result, .. } => result.clone(),
+            Expr::SourceFile { source_position: result, .. } => result.clone(),
+            Expr::Slice { source_position: result, .. } => result.clone(),
             Expr::StringLiteral { source_position: result, .. } => result.clone(),
             Expr::Subtraction { source_position: result, .. } => result.clone(),
             Expr::TrailingDocumentation { source_position: result, .. } => result.clone(),
@@ -122,5 +184,130 @@ impl Expr {
             Expr::Where { source_position: result, .. } => result.clone(),
         }
     }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the tag naming the kind of this expression, matching the tags of the TypeScript AST where the two agree.
+    pub fn tag(&self) -> &'static str {
+        return match self {
+            Expr::Addition { .. } => "#AdditionExpr",
+            Expr::ArrayLiteral { .. } => "#ArrayLiteralExpr",
+            Expr::As { .. } => "#AsExpr",
+            Expr::BooleanLiteral { .. } => "#BooleanLiteralExpr",
+            Expr::BuiltInType { .. } => "#BuiltInTypeExpr",
+            Expr::Division { .. } => "#DivisionExpr",
+            Expr::Document { .. } => "#DocumentExpr",
+            Expr::Equals { .. } => "#EqualsExpr",
+            Expr::Error { .. } => "#ErrorExpr",
+            Expr::FieldReference { .. } => "#FieldReferenceExpr",
+            Expr::Float64Literal { .. } => "#Float64LiteralExpr",
+            Expr::FunctionArguments { .. } => "#FunctionArgumentsExpr",
+            Expr::FunctionArrow { .. } => "#FunctionArrowExpr",
+            Expr::FunctionCall { .. } => "#FunctionCallExpr",
+            Expr::GreaterThan { .. } => "#GreaterThanExpr",
+            Expr::GreaterThanOrEquals { .. } => "#GreaterThanOrEqualsExpr",
+            Expr::Identifier { .. } => "#IdentifierExpr",
+            Expr::In { .. } => "#InExpr",
+            Expr::Index { .. } => "#IndexExpr",
+            Expr::Int64Literal { .. } => "#Int64LiteralExpr",
+            Expr::Intersect { .. } => "#IntersectExpr",
+            Expr::IntersectAssignValue { .. } => "#IntersectAssignValueExpr",
+            Expr::IntersectDefaultValue { .. } => "#IntersectDefaultValueExpr",
+            Expr::IntersectLowPrecedence { .. } => "#IntersectLowPrecedenceExpr",
+            Expr::Is { .. } => "#IsExpr",
+            Expr::LeadingDocumentation { .. } => "#LeadingDocumentationExpr",
+            Expr::LessThan { .. } => "#LessThanExpr",
+            Expr::LessThanOrEquals { .. } => "#LessThanOrEqualsExpr",
+            Expr::LogicalAnd { .. } => "#LogicalAndExpr",
+            Expr::LogicalNotOperation { .. } => "#LogicalNotExpr",
+            Expr::LogicalOr { .. } => "#LogicalOrExpr",
+            Expr::Match { .. } => "#MatchExpr",
+            Expr::Multiplication { .. } => "#MultiplicationExpr",
+            Expr::NegationOperation { .. } => "#NegationExpr",
+            Expr::NotEquals { .. } => "#NotEqualsExpr",
+            Expr::NotMatch { .. } => "#NotMatchExpr",
+            Expr::Optional { .. } => "#OptionalExpr",
+            Expr::Parenthesized { .. } => "#ParenthesizedExpr",
+            Expr::Qualify { .. } => "#QualificationExpr",
+            Expr::Range { .. } => "#RangeExpr",
+            Expr::Record { .. } => "#RecordExpr",
+            Expr::Slice { .. } => "#SliceExpr",
+            Expr::SourceFile { .. } => "#SourceFileExpr",
+            Expr::StringLiteral { delimiters, .. } => delimiters.tag(),
+            Expr::Subtraction { .. } => "#SubtractionExpr",
+            Expr::TrailingDocumentation { .. } => "#TrailingDocumentationExpr",
+            Expr::Union { .. } => "#UnionExpr",
+            Expr::Unit { .. } => "#UnitExpr",
+            Expr::When { .. } => "#WhenExpr",
+            Expr::Where { .. } => "#WhereExpr",
+        };
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the child expressions of this expression in source order.
+    pub fn children(&self) -> Vec<&Expr> {
+        return match self {
+            Expr::ArrayLiteral { elements: items, .. } |
+            Expr::FunctionArguments { items, .. } |
+            Expr::Record { items, .. } |
+            Expr::SourceFile { items, .. } =>
+                items.iter().map(|item| item.as_ref()).collect(),
+
+            Expr::FieldReference { parent: lhs, child: rhs, .. } |
+            Expr::FunctionArrow { argument: lhs, result: rhs, .. } |
+            Expr::FunctionCall { function_reference: lhs, argument: rhs, .. } |
+            Expr::Index { operand: lhs, index: rhs, .. } |
+            Expr::Range { first: lhs, last: rhs, .. } |
+            Expr::Addition { lhs, rhs, .. } |
+            Expr::As { lhs, rhs, .. } |
+            Expr::Division { lhs, rhs, .. } |
+            Expr::Document { lhs, rhs, .. } |
+            Expr::Equals { lhs, rhs, .. } |
+            Expr::GreaterThan { lhs, rhs, .. } |
+            Expr::GreaterThanOrEquals { lhs, rhs, .. } |
+            Expr::In { lhs, rhs, .. } |
+            Expr::Intersect { lhs, rhs, .. } |
+            Expr::IntersectAssignValue { lhs, rhs, .. } |
+            Expr::IntersectDefaultValue { lhs, rhs, .. } |
+            Expr::IntersectLowPrecedence { lhs, rhs, .. } |
+            Expr::Is { lhs, rhs, .. } |
+            Expr::LessThan { lhs, rhs, .. } |
+            Expr::LessThanOrEquals { lhs, rhs, .. } |
+            Expr::LogicalAnd { lhs, rhs, .. } |
+            Expr::LogicalOr { lhs, rhs, .. } |
+            Expr::Match { lhs, rhs, .. } |
+            Expr::Multiplication { lhs, rhs, .. } |
+            Expr::NotEquals { lhs, rhs, .. } |
+            Expr::NotMatch { lhs, rhs, .. } |
+            Expr::Qualify { lhs, rhs, .. } |
+            Expr::Subtraction { lhs, rhs, .. } |
+            Expr::Union { lhs, rhs, .. } |
+            Expr::When { lhs, rhs, .. } |
+            Expr::Where { lhs, rhs, .. } =>
+                vec![lhs, rhs],
+
+            Expr::LogicalNotOperation { operand, .. } |
+            Expr::NegationOperation { operand, .. } |
+            Expr::Optional { operand, .. } |
+            Expr::Parenthesized { inner_expr: operand, .. } =>
+                vec![operand],
+
+            Expr::Slice { operand, first, last, .. } =>
+                vec![operand, first, last],
+
+            Expr::BooleanLiteral { .. } |
+            Expr::BuiltInType { .. } |
+            Expr::Error { .. } |
+            Expr::Float64Literal { .. } |
+            Expr::Identifier { .. } |
+            Expr::Int64Literal { .. } |
+            Expr::LeadingDocumentation { .. } |
+            Expr::StringLiteral { .. } |
+            Expr::TrailingDocumentation { .. } |
+            Expr::Unit { .. } =>
+                vec![],
+        };
+    }
 }
 
@@ -0,0 +1,521 @@
+//
+// # JSON (serde) representation of Lligne expression trees.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+// Every node is an object with a "tag" naming its kind (the same tags as the TypeScript AST, see Expr::tag), a
+// "sourcePos" holding its "startOffset" and "endOffset", and then the fields of its kind:
+//
+//   - binary operations (e.g. #AdditionExpr, #QualificationExpr): "lhs" and "rhs"
+//   - #FieldReferenceExpr: "parent" and "child"
+//   - #FunctionArrowExpr: "argument" and "result"
+//   - #FunctionCallExpr: "functionReference" and "argument"
+//   - #IndexExpr: "operand" and "index"
+//   - #RangeExpr: "first" and "last"
+//   - #SliceExpr: "operand", "first", and "last"
+//   - #LogicalNotExpr, #NegationExpr, #OptionalExpr: "operand"
+//   - #ParenthesizedExpr: "innerExpr"
+//   - #ArrayLiteralExpr: "elements"; #FunctionArgumentsExpr, #RecordExpr, #SourceFileExpr: "items"
+//   - #BooleanLiteralExpr, #Float64LiteralExpr, #Int64LiteralExpr: "value"
+//   - string literals (e.g. #DoubleQuotedStringExpr): "value", decoded; the tag gives the delimiters
+//   - identifiers, built-in types, documentation, errors, and #UnitExpr: nothing more (their text is in the source)
+//
+// For example, "x + 1" is
+//
+//   {"tag":"#AdditionExpr","sourcePos":{"startOffset":0,"endOffset":5},
+//    "lhs":{"tag":"#IdentifierExpr","sourcePos":{"startOffset":0,"endOffset":1}},
+//    "rhs":{"tag":"#Int64LiteralExpr","sourcePos":{"startOffset":4,"endOffset":5},"value":1}}
+//
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use serde::ser::SerializeMap;
+use shared_vector::{SharedVector, Vector};
+use crate::lligne::code::parsing::expressions::{Expr, StringDelimiters};
+use crate::lligne::code::util::source_pos::SourcePos;
+
+//=====================================================================================================================
+
+impl Serialize for Expr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+
+        map.serialize_entry("tag", self.tag())?;
+        map.serialize_entry("sourcePos", &self.get_source_position())?;
+
+        match self {
+            Expr::ArrayLiteral { elements, .. } =>
+                map.serialize_entry("elements", elements.as_slice())?,
+
+            Expr::FunctionArguments { items, .. } |
+            Expr::Record { items, .. } |
+            Expr::SourceFile { items, .. } =>
+                map.serialize_entry("items", items.as_slice())?,
+
+            Expr::Addition { lhs, rhs, .. } |
+            Expr::As { lhs, rhs, .. } |
+            Expr::Division { lhs, rhs, .. } |
+            Expr::Document { lhs, rhs, .. } |
+            Expr::Equals { lhs, rhs, .. } |
+            Expr::GreaterThan { lhs, rhs, .. } |
+            Expr::GreaterThanOrEquals { lhs, rhs, .. } |
+            Expr::In { lhs, rhs, .. } |
+            Expr::Intersect { lhs, rhs, .. } |
+            Expr::IntersectAssignValue { lhs, rhs, .. } |
+            Expr::IntersectDefaultValue { lhs, rhs, .. } |
+            Expr::IntersectLowPrecedence { lhs, rhs, .. } |
+            Expr::Is { lhs, rhs, .. } |
+            Expr::LessThan { lhs, rhs, .. } |
+            Expr::LessThanOrEquals { lhs, rhs, .. } |
+            Expr::LogicalAnd { lhs, rhs, .. } |
+            Expr::LogicalOr { lhs, rhs, .. } |
+            Expr::Match { lhs, rhs, .. } |
+            Expr::Multiplication { lhs, rhs, .. } |
+            Expr::NotEquals { lhs, rhs, .. } |
+            Expr::NotMatch { lhs, rhs, .. } |
+            Expr::Qualify { lhs, rhs, .. } |
+            Expr::Subtraction { lhs, rhs, .. } |
+            Expr::Union { lhs, rhs, .. } |
+            Expr::When { lhs, rhs, .. } |
+            Expr::Where { lhs, rhs, .. } => {
+                map.serialize_entry("lhs", lhs)?;
+                map.serialize_entry("rhs", rhs)?;
+            }
+
+            Expr::BooleanLiteral { value, .. } =>
+                map.serialize_entry("value", value)?,
+
+            Expr::FieldReference { parent, child, .. } => {
+                map.serialize_entry("parent", parent)?;
+                map.serialize_entry("child", child)?;
+            }
+
+            Expr::Float64Literal { value, .. } =>
+                map.serialize_entry("value", value)?,
+
+            Expr::FunctionArrow { argument, result, .. } => {
+                map.serialize_entry("argument", argument)?;
+                map.serialize_entry("result", result)?;
+            }
+
+            Expr::FunctionCall { function_reference, argument, .. } => {
+                map.serialize_entry("functionReference", function_reference)?;
+                map.serialize_entry("argument", argument)?;
+            }
+
+            Expr::Index { operand, index, .. } => {
+                map.serialize_entry("operand", operand)?;
+                map.serialize_entry("index", index)?;
+            }
+
+            Expr::Int64Literal { value, .. } =>
+                map.serialize_entry("value", value)?,
+
+            Expr::LogicalNotOperation { operand, .. } |
+            Expr::NegationOperation { operand, .. } |
+            Expr::Optional { operand, .. } =>
+                map.serialize_entry("operand", operand)?,
+
+            Expr::Parenthesized { inner_expr, .. } =>
+                map.serialize_entry("innerExpr", inner_expr)?,
+
+            Expr::Range { first, last, .. } => {
+                map.serialize_entry("first", first)?;
+                map.serialize_entry("last", last)?;
+            }
+
+            Expr::Slice { operand, first, last, .. } => {
+                map.serialize_entry("operand", operand)?;
+                map.serialize_entry("first", first)?;
+                map.serialize_entry("last", last)?;
+            }
+
+            Expr::StringLiteral { value, .. } =>
+                map.serialize_entry("value", value)?,
+
+            Expr::BuiltInType { .. } |
+            Expr::Error { .. } |
+            Expr::Identifier { .. } |
+            Expr::LeadingDocumentation { .. } |
+            Expr::TrailingDocumentation { .. } |
+            Expr::Unit { .. } => {}
+        }
+
+        return map.end();
+    }
+}
+
+//=====================================================================================================================
+
+impl<'de> Deserialize<'de> for Expr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Expr, D::Error> {
+        let fields = ExprFields::deserialize(deserializer)?;
+        let tag = fields.tag.clone();
+
+        return fields.into_expr().map_err(|message| D::Error::custom(format!("{} ({})", message, tag)));
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// The union of the fields of all kinds of node, read before the tag decides which of them are needed.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExprFields {
+    tag: String,
+    source_pos: SourcePos,
+    argument: Option<Box<Expr>>,
+    child: Option<Box<Expr>>,
+    elements: Option<Vec<Expr>>,
+    first: Option<Box<Expr>>,
+    function_reference: Option<Box<Expr>>,
+    index: Option<Box<Expr>>,
+    inner_expr: Option<Box<Expr>>,
+    items: Option<Vec<Expr>>,
+    last: Option<Box<Expr>>,
+    lhs: Option<Box<Expr>>,
+    operand: Option<Box<Expr>>,
+    parent: Option<Box<Expr>>,
+    result: Option<Box<Expr>>,
+    rhs: Option<Box<Expr>>,
+    value: Option<LiteralValue>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// The value of a literal, whose type depends on the tag.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LiteralValue {
+    Boolean(bool),
+    Int64(i64),
+    Float64(f64),
+    String(String),
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl LiteralValue {
+    fn into_bool(self) -> Result<bool, String> {
+        return match self {
+            LiteralValue::Boolean(value) => Ok(value),
+            _ => Err("Expected a boolean value".to_string()),
+        };
+    }
+
+    fn into_f64(self) -> Result<f64, String> {
+        return match self {
+            LiteralValue::Float64(value) => Ok(value),
+            LiteralValue::Int64(value) => Ok(value as f64),
+            _ => Err("Expected a floating point value".to_string()),
+        };
+    }
+
+    fn into_i64(self) -> Result<i64, String> {
+        return match self {
+            LiteralValue::Int64(value) => Ok(value),
+            _ => Err("Expected an integer value".to_string()),
+        };
+    }
+
+    fn into_string(self) -> Result<String, String> {
+        return match self {
+            LiteralValue::String(value) => Ok(value),
+            _ => Err("Expected a string value".to_string()),
+        };
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl ExprFields {
+    // Builds the expression named by the tag from the fields it requires.
+    fn into_expr(self) -> Result<Expr, String> {
+        let source_position = self.source_pos;
+
+        if let Some(delimiters) = StringDelimiters::from_tag(&self.tag) {
+            return Ok(Expr::StringLiteral {
+                source_position,
+                delimiters,
+                value: required(self.value, "value")?.into_string()?,
+            });
+        }
+
+        return Ok(match self.tag.as_str() {
+            "#AdditionExpr" => Expr::Addition {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#ArrayLiteralExpr" => Expr::ArrayLiteral {
+                source_position,
+                elements: to_shared(required(self.elements, "elements")?),
+            },
+            "#AsExpr" => Expr::As { source_position, lhs: required(self.lhs, "lhs")?, rhs: required(self.rhs, "rhs")? },
+            "#BooleanLiteralExpr" => Expr::BooleanLiteral {
+                source_position,
+                value: required(self.value, "value")?.into_bool()?,
+            },
+            "#BuiltInTypeExpr" => Expr::BuiltInType { source_position },
+            "#DivisionExpr" => Expr::Division {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#DocumentExpr" => Expr::Document {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#EqualsExpr" => Expr::Equals {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#ErrorExpr" => Expr::Error { source_position },
+            "#FieldReferenceExpr" => Expr::FieldReference {
+                source_position,
+                parent: required(self.parent, "parent")?,
+                child: required(self.child, "child")?,
+            },
+            "#Float64LiteralExpr" => Expr::Float64Literal {
+                source_position,
+                value: required(self.value, "value")?.into_f64()?,
+            },
+            "#FunctionArgumentsExpr" => Expr::FunctionArguments {
+                source_position,
+                items: to_shared(required(self.items, "items")?),
+            },
+            "#FunctionArrowExpr" => Expr::FunctionArrow {
+                source_position,
+                argument: required(self.argument, "argument")?,
+                result: required(self.result, "result")?,
+            },
+            "#FunctionCallExpr" => Expr::FunctionCall {
+                source_position,
+                function_reference: required(self.function_reference, "functionReference")?,
+                argument: required(self.argument, "argument")?,
+            },
+            "#GreaterThanExpr" => Expr::GreaterThan {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#GreaterThanOrEqualsExpr" => Expr::GreaterThanOrEquals {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#IdentifierExpr" => Expr::Identifier { source_position },
+            "#InExpr" => Expr::In { source_position, lhs: required(self.lhs, "lhs")?, rhs: required(self.rhs, "rhs")? },
+            "#IndexExpr" => Expr::Index {
+                source_position,
+                operand: required(self.operand, "operand")?,
+                index: required(self.index, "index")?,
+            },
+            "#Int64LiteralExpr" => Expr::Int64Literal {
+                source_position,
+                value: required(self.value, "value")?.into_i64()?,
+            },
+            "#IntersectExpr" => Expr::Intersect {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#IntersectAssignValueExpr" => Expr::IntersectAssignValue {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#IntersectDefaultValueExpr" => Expr::IntersectDefaultValue {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#IntersectLowPrecedenceExpr" => Expr::IntersectLowPrecedence {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#IsExpr" => Expr::Is { source_position, lhs: required(self.lhs, "lhs")?, rhs: required(self.rhs, "rhs")? },
+            "#LeadingDocumentationExpr" => Expr::LeadingDocumentation { source_position },
+            "#LessThanExpr" => Expr::LessThan {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#LessThanOrEqualsExpr" => Expr::LessThanOrEquals {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#LogicalAndExpr" => Expr::LogicalAnd {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#LogicalNotExpr" => Expr::LogicalNotOperation {
+                source_position,
+                operand: required(self.operand, "operand")?,
+            },
+            "#LogicalOrExpr" => Expr::LogicalOr {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#MatchExpr" => Expr::Match {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#MultiplicationExpr" => Expr::Multiplication {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#NegationExpr" => Expr::NegationOperation { source_position, operand: required(self.operand, "operand")? },
+            "#NotEqualsExpr" => Expr::NotEquals {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#NotMatchExpr" => Expr::NotMatch {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#OptionalExpr" => Expr::Optional { source_position, operand: required(self.operand, "operand")? },
+            "#ParenthesizedExpr" => Expr::Parenthesized {
+                source_position,
+                inner_expr: required(self.inner_expr, "innerExpr")?,
+            },
+            "#QualificationExpr" => Expr::Qualify {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#RangeExpr" => Expr::Range {
+                source_position,
+                first: required(self.first, "first")?,
+                last: required(self.last, "last")?,
+            },
+            "#RecordExpr" => Expr::Record { source_position, items: to_shared(required(self.items, "items")?) },
+            "#SliceExpr" => Expr::Slice {
+                source_position,
+                operand: required(self.operand, "operand")?,
+                first: required(self.first, "first")?,
+                last: required(self.last, "last")?,
+            },
+            "#SourceFileExpr" => Expr::SourceFile { source_position, items: to_shared(required(self.items, "items")?) },
+            "#SubtractionExpr" => Expr::Subtraction {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#TrailingDocumentationExpr" => Expr::TrailingDocumentation { source_position },
+            "#UnionExpr" => Expr::Union {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#UnitExpr" => Expr::Unit { source_position },
+            "#WhenExpr" => Expr::When {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            "#WhereExpr" => Expr::Where {
+                source_position,
+                lhs: required(self.lhs, "lhs")?,
+                rhs: required(self.rhs, "rhs")?,
+            },
+            _ => return Err("Unknown expression tag".to_string()),
+        });
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Unwraps a field required by the tag of the node being read.
+fn required<T>(field: Option<T>, name: &str) -> Result<T, String> {
+    return field.ok_or_else(|| format!("Missing field '{}'", name));
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Converts a deserialized list of child expressions to the shared vector used in the tree.
+fn to_shared(exprs: Vec<Expr>) -> SharedVector<Box<Expr>> {
+    let mut result: Vector<Box<Expr>> = Vector::new();
+    for expr in exprs {
+        result.push(Box::new(expr));
+    }
+    return result.into_shared();
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::lligne::code::parsing::parser;
+    use crate::lligne::code::scanning::scanner;
+    use super::*;
+
+    fn check_round_trip(source_code: &str) {
+        let scan_result = scanner::scan(source_code);
+        let parse_result = parser::parse_source_file(&scan_result);
+
+        let json = serde_json::to_string(&parse_result.model()).unwrap();
+        let expr: Expr = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(json, serde_json::to_string(&expr).unwrap(), "Round trip failed for '{}'", source_code);
+    }
+
+    #[test]
+    fn test_json_shape() {
+        let scan_result = scanner::scan("x + 1");
+        let parse_result = parser::parse_expression(&scan_result);
+
+        assert_eq!(
+            r##"{"tag":"#AdditionExpr","sourcePos":{"startOffset":0,"endOffset":5},"##.to_string() +
+                r##""lhs":{"tag":"#IdentifierExpr","sourcePos":{"startOffset":0,"endOffset":1}},"## +
+                r##""rhs":{"tag":"#Int64LiteralExpr","sourcePos":{"startOffset":4,"endOffset":5},"value":1}}"##,
+            serde_json::to_string(&parse_result.model()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_string_literal_shape() {
+        let scan_result = scanner::scan("'a\\tb'");
+        let parse_result = parser::parse_expression(&scan_result);
+
+        assert_eq!(
+            r##"{"tag":"#SingleQuotedStringExpr","sourcePos":{"startOffset":0,"endOffset":6},"value":"a\tb"}"##,
+            serde_json::to_string(&parse_result.model()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_round_trips() {
+        check_round_trip("x: int64 = 3 + 4 * -y, z = f(a, b) -> c?");
+        check_round_trip("r = {a: [1, 2.5, 6E23], b: (true), c: ()}");
+        check_round_trip("s = xs[1..3].name[0] as string when not (x == 'c' || y != \"d\")");
+        check_round_trip("doc = `line one\n  `line two\nt &? u &: v && w | q in r is s =~ t !~ u where v");
+        check_round_trip("e = [1, , 3]");
+    }
+
+    #[test]
+    fn test_deserialize_errors() {
+        let missing = serde_json::from_str::<Expr>(
+            r##"{"tag":"#AdditionExpr","sourcePos":{"startOffset":0,"endOffset":5}}"##
+        );
+        assert!(missing.unwrap_err().to_string().contains("Missing field 'lhs' (#AdditionExpr)"));
+
+        let unknown = serde_json::from_str::<Expr>(
+            r##"{"tag":"#NoSuchExpr","sourcePos":{"startOffset":0,"endOffset":5}}"##
+        );
+        assert!(unknown.unwrap_err().to_string().contains("Unknown expression tag (#NoSuchExpr)"));
+    }
+}
+
+//=====================================================================================================================
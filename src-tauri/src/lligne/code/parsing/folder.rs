@@ -0,0 +1,433 @@
+//
+// # Rewriting of Lligne expression trees.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+
+use shared_vector::{SharedVector, Vector};
+use crate::lligne::code::parsing::expressions::{Expr, StringDelimiters};
+use crate::lligne::code::util::source_pos::SourcePos;
+
+//=====================================================================================================================
+
+// Rebuilds an expression tree by value. Each kind of expression has its own method receiving the fields of the node
+// and returning its replacement; by default it folds the children in source order and rebuilds the same kind of
+// node from them, so an implementation overrides only the methods for the kinds of expression it rewrites.
+// Overriding fold_expr sees every node; it should call walk_expr to continue the rewrite.
+pub trait Folder {
+    // Takes the box of a child as it comes from its parent so that a replacement may reuse it.
+    #[allow(clippy::boxed_local)]
+    fn fold_expr(&mut self, expr: Box<Expr>) -> Box<Expr> {
+        return walk_expr(self, *expr);
+    }
+
+    fn fold_exprs(&mut self, exprs: SharedVector<Box<Expr>>) -> SharedVector<Box<Expr>> {
+        let mut exprs = exprs.into_unique();
+        let mut result: Vector<Box<Expr>> = Vector::with_capacity(exprs.len());
+        for expr in exprs.drain(..) {
+            result.push(self.fold_expr(expr));
+        }
+        return result.into_shared();
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    fn fold_addition(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Addition { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_array_literal(&mut self, source_position: SourcePos, elements: SharedVector<Box<Expr>>) -> Box<Expr> {
+        return Box::new(Expr::ArrayLiteral { source_position, elements: self.fold_exprs(elements) });
+    }
+
+    fn fold_as(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::As { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_boolean_literal(&mut self, source_position: SourcePos, value: bool) -> Box<Expr> {
+        return Box::new(Expr::BooleanLiteral { source_position, value });
+    }
+
+    fn fold_built_in_type(&mut self, source_position: SourcePos) -> Box<Expr> {
+        return Box::new(Expr::BuiltInType { source_position });
+    }
+
+    fn fold_division(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Division { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_document(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Document { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_equals(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Equals { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_error(&mut self, source_position: SourcePos) -> Box<Expr> {
+        return Box::new(Expr::Error { source_position });
+    }
+
+    fn fold_field_reference(&mut self, source_position: SourcePos, parent: Box<Expr>, child: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::FieldReference {
+            source_position,
+            parent: self.fold_expr(parent),
+            child: self.fold_expr(child),
+        });
+    }
+
+    fn fold_float64_literal(&mut self, source_position: SourcePos, value: f64) -> Box<Expr> {
+        return Box::new(Expr::Float64Literal { source_position, value });
+    }
+
+    fn fold_function_arguments(&mut self, source_position: SourcePos, items: SharedVector<Box<Expr>>) -> Box<Expr> {
+        return Box::new(Expr::FunctionArguments { source_position, items: self.fold_exprs(items) });
+    }
+
+    fn fold_function_arrow(&mut self, source_position: SourcePos, argument: Box<Expr>, result: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::FunctionArrow {
+            source_position,
+            argument: self.fold_expr(argument),
+            result: self.fold_expr(result),
+        });
+    }
+
+    fn fold_function_call(
+        &mut self,
+        source_position: SourcePos,
+        function_reference: Box<Expr>,
+        argument: Box<Expr>,
+    ) -> Box<Expr> {
+        return Box::new(Expr::FunctionCall {
+            source_position,
+            function_reference: self.fold_expr(function_reference),
+            argument: self.fold_expr(argument),
+        });
+    }
+
+    fn fold_greater_than(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::GreaterThan { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_greater_than_or_equals(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::GreaterThanOrEquals {
+            source_position,
+            lhs: self.fold_expr(lhs),
+            rhs: self.fold_expr(rhs),
+        });
+    }
+
+    fn fold_identifier(&mut self, source_position: SourcePos) -> Box<Expr> {
+        return Box::new(Expr::Identifier { source_position });
+    }
+
+    fn fold_in(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::In { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_index(&mut self, source_position: SourcePos, operand: Box<Expr>, index: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Index {
+            source_position,
+            operand: self.fold_expr(operand),
+            index: self.fold_expr(index),
+        });
+    }
+
+    fn fold_int64_literal(&mut self, source_position: SourcePos, value: i64) -> Box<Expr> {
+        return Box::new(Expr::Int64Literal { source_position, value });
+    }
+
+    fn fold_intersect(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Intersect { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_intersect_assign_value(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::IntersectAssignValue {
+            source_position,
+            lhs: self.fold_expr(lhs),
+            rhs: self.fold_expr(rhs),
+        });
+    }
+
+    fn fold_intersect_default_value(
+        &mut self,
+        source_position: SourcePos,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    ) -> Box<Expr> {
+        return Box::new(Expr::IntersectDefaultValue {
+            source_position,
+            lhs: self.fold_expr(lhs),
+            rhs: self.fold_expr(rhs),
+        });
+    }
+
+    fn fold_intersect_low_precedence(
+        &mut self,
+        source_position: SourcePos,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    ) -> Box<Expr> {
+        return Box::new(Expr::IntersectLowPrecedence {
+            source_position,
+            lhs: self.fold_expr(lhs),
+            rhs: self.fold_expr(rhs),
+        });
+    }
+
+    fn fold_is(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Is { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_leading_documentation(&mut self, source_position: SourcePos) -> Box<Expr> {
+        return Box::new(Expr::LeadingDocumentation { source_position });
+    }
+
+    fn fold_less_than(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::LessThan { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_less_than_or_equals(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::LessThanOrEquals { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_logical_and(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::LogicalAnd { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_logical_not_operation(&mut self, source_position: SourcePos, operand: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::LogicalNotOperation { source_position, operand: self.fold_expr(operand) });
+    }
+
+    fn fold_logical_or(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::LogicalOr { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_match(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Match { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_multiplication(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Multiplication { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_negation_operation(&mut self, source_position: SourcePos, operand: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::NegationOperation { source_position, operand: self.fold_expr(operand) });
+    }
+
+    fn fold_not_equals(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::NotEquals { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_not_match(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::NotMatch { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_optional(&mut self, source_position: SourcePos, operand: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Optional { source_position, operand: self.fold_expr(operand) });
+    }
+
+    fn fold_parenthesized(&mut self, source_position: SourcePos, inner_expr: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Parenthesized { source_position, inner_expr: self.fold_expr(inner_expr) });
+    }
+
+    fn fold_qualify(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Qualify { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_range(&mut self, source_position: SourcePos, first: Box<Expr>, last: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Range { source_position, first: self.fold_expr(first), last: self.fold_expr(last) });
+    }
+
+    fn fold_record(&mut self, source_position: SourcePos, items: SharedVector<Box<Expr>>) -> Box<Expr> {
+        return Box::new(Expr::Record { source_position, items: self.fold_exprs(items) });
+    }
+
+    fn fold_slice(
+        &mut self,
+        source_position: SourcePos,
+        operand: Box<Expr>,
+        first: Box<Expr>,
+        last: Box<Expr>,
+    ) -> Box<Expr> {
+        return Box::new(Expr::Slice {
+            source_position,
+            operand: self.fold_expr(operand),
+            first: self.fold_expr(first),
+            last: self.fold_expr(last),
+        });
+    }
+
+    fn fold_source_file(&mut self, source_position: SourcePos, items: SharedVector<Box<Expr>>) -> Box<Expr> {
+        return Box::new(Expr::SourceFile { source_position, items: self.fold_exprs(items) });
+    }
+
+    fn fold_string_literal(
+        &mut self,
+        source_position: SourcePos,
+        delimiters: StringDelimiters,
+        value: String,
+    ) -> Box<Expr> {
+        return Box::new(Expr::StringLiteral { source_position, delimiters, value });
+    }
+
+    fn fold_subtraction(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Subtraction { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_trailing_documentation(&mut self, source_position: SourcePos) -> Box<Expr> {
+        return Box::new(Expr::TrailingDocumentation { source_position });
+    }
+
+    fn fold_union(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Union { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_unit(&mut self, source_position: SourcePos) -> Box<Expr> {
+        return Box::new(Expr::Unit { source_position });
+    }
+
+    fn fold_when(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::When { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+
+    fn fold_where(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        return Box::new(Expr::Where { source_position, lhs: self.fold_expr(lhs), rhs: self.fold_expr(rhs) });
+    }
+}
+
+//=====================================================================================================================
+
+// Dispatches a node to the folder method for its kind of expression.
+pub fn walk_expr<F: Folder + ?Sized>(folder: &mut F, expr: Expr) -> Box<Expr> {
+    return match expr {
+        Expr::Addition { source_position, lhs, rhs } => folder.fold_addition(source_position, lhs, rhs),
+        Expr::ArrayLiteral { source_position, elements } => folder.fold_array_literal(source_position, elements),
+        Expr::As { source_position, lhs, rhs } => folder.fold_as(source_position, lhs, rhs),
+        Expr::BooleanLiteral { source_position, value } => folder.fold_boolean_literal(source_position, value),
+        Expr::BuiltInType { source_position } => folder.fold_built_in_type(source_position),
+        Expr::Division { source_position, lhs, rhs } => folder.fold_division(source_position, lhs, rhs),
+        Expr::Document { source_position, lhs, rhs } => folder.fold_document(source_position, lhs, rhs),
+        Expr::Equals { source_position, lhs, rhs } => folder.fold_equals(source_position, lhs, rhs),
+        Expr::Error { source_position } => folder.fold_error(source_position),
+        Expr::FieldReference { source_position, parent, child } =>
+            folder.fold_field_reference(source_position, parent, child),
+        Expr::Float64Literal { source_position, value } => folder.fold_float64_literal(source_position, value),
+        Expr::FunctionArguments { source_position, items } => folder.fold_function_arguments(source_position, items),
+        Expr::FunctionArrow { source_position, argument, result } =>
+            folder.fold_function_arrow(source_position, argument, result),
+        Expr::FunctionCall { source_position, function_reference, argument } =>
+            folder.fold_function_call(source_position, function_reference, argument),
+        Expr::GreaterThan { source_position, lhs, rhs } => folder.fold_greater_than(source_position, lhs, rhs),
+        Expr::GreaterThanOrEquals { source_position, lhs, rhs } =>
+            folder.fold_greater_than_or_equals(source_position, lhs, rhs),
+        Expr::Identifier { source_position } => folder.fold_identifier(source_position),
+        Expr::In { source_position, lhs, rhs } => folder.fold_in(source_position, lhs, rhs),
+        Expr::Index { source_position, operand, index } => folder.fold_index(source_position, operand, index),
+        Expr::Int64Literal { source_position, value } => folder.fold_int64_literal(source_position, value),
+        Expr::Intersect { source_position, lhs, rhs } => folder.fold_intersect(source_position, lhs, rhs),
+        Expr::IntersectAssignValue { source_position, lhs, rhs } =>
+            folder.fold_intersect_assign_value(source_position, lhs, rhs),
+        Expr::IntersectDefaultValue { source_position, lhs, rhs } =>
+            folder.fold_intersect_default_value(source_position, lhs, rhs),
+        Expr::IntersectLowPrecedence { source_position, lhs, rhs } =>
+            folder.fold_intersect_low_precedence(source_position, lhs, rhs),
+        Expr::Is { source_position, lhs, rhs } => folder.fold_is(source_position, lhs, rhs),
+        Expr::LeadingDocumentation { source_position } => folder.fold_leading_documentation(source_position),
+        Expr::LessThan { source_position, lhs, rhs } => folder.fold_less_than(source_position, lhs, rhs),
+        Expr::LessThanOrEquals { source_position, lhs, rhs } =>
+            folder.fold_less_than_or_equals(source_position, lhs, rhs),
+        Expr::LogicalAnd { source_position, lhs, rhs } => folder.fold_logical_and(source_position, lhs, rhs),
+        Expr::LogicalNotOperation { source_position, operand } =>
+            folder.fold_logical_not_operation(source_position, operand),
+        Expr::LogicalOr { source_position, lhs, rhs } => folder.fold_logical_or(source_position, lhs, rhs),
+        Expr::Match { source_position, lhs, rhs } => folder.fold_match(source_position, lhs, rhs),
+        Expr::Multiplication { source_position, lhs, rhs } => folder.fold_multiplication(source_position, lhs, rhs),
+        Expr::NegationOperation { source_position, operand } =>
+            folder.fold_negation_operation(source_position, operand),
+        Expr::NotEquals { source_position, lhs, rhs } => folder.fold_not_equals(source_position, lhs, rhs),
+        Expr::NotMatch { source_position, lhs, rhs } => folder.fold_not_match(source_position, lhs, rhs),
+        Expr::Optional { source_position, operand } => folder.fold_optional(source_position, operand),
+        Expr::Parenthesized { source_position, inner_expr } => folder.fold_parenthesized(source_position, inner_expr),
+        Expr::Qualify { source_position, lhs, rhs } => folder.fold_qualify(source_position, lhs, rhs),
+        Expr::Range { source_position, first, last } => folder.fold_range(source_position, first, last),
+        Expr::Record { source_position, items } => folder.fold_record(source_position, items),
+        Expr::Slice { source_position, operand, first, last } =>
+            folder.fold_slice(source_position, operand, first, last),
+        Expr::SourceFile { source_position, items } => folder.fold_source_file(source_position, items),
+        Expr::StringLiteral { source_position, delimiters, value } =>
+            folder.fold_string_literal(source_position, delimiters, value),
+        Expr::Subtraction { source_position, lhs, rhs } => folder.fold_subtraction(source_position, lhs, rhs),
+        Expr::TrailingDocumentation { source_position } => folder.fold_trailing_documentation(source_position),
+        Expr::Union { source_position, lhs, rhs } => folder.fold_union(source_position, lhs, rhs),
+        Expr::Unit { source_position } => folder.fold_unit(source_position),
+        Expr::When { source_position, lhs, rhs } => folder.fold_when(source_position, lhs, rhs),
+        Expr::Where { source_position, lhs, rhs } => folder.fold_where(source_position, lhs, rhs),
+    };
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::lligne::code::parsing::parser;
+    use crate::lligne::code::parsing::s_expressions::to_compact_s_expression;
+    use crate::lligne::code::scanning::scanner;
+    use super::*;
+
+    // Replaces the sum of two integer literals with its value, innermost sums first.
+    struct ConstantFolder;
+
+    impl Folder for ConstantFolder {
+        fn fold_addition(&mut self, source_position: SourcePos, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+            let lhs = self.fold_expr(lhs);
+            let rhs = self.fold_expr(rhs);
+
+            if let (Expr::Int64Literal { value: a, .. }, Expr::Int64Literal { value: b, .. }) = (&*lhs, &*rhs) {
+                if let Some(value) = a.checked_add(*b) {
+                    return Box::new(Expr::Int64Literal { source_position, value });
+                }
+            }
+
+            return Box::new(Expr::Addition { source_position, lhs, rhs });
+        }
+    }
+
+    #[test]
+    fn test_folder() {
+        let source_code = "{a: 1 + 2 + 3, b: [x + (1 + 1)], c: f(4 + 5) + y}";
+        let scan_result = scanner::scan(source_code);
+        let expression = parser::parse_expression(&scan_result);
+
+        let folded = ConstantFolder.fold_expr(expression.model());
+
+        assert_eq!(
+            "(#RecordExpr \
+               (#QualificationExpr (#IdentifierExpr a) (#Int64LiteralExpr 6)) \
+               (#QualificationExpr (#IdentifierExpr b) \
+                 (#ArrayLiteralExpr (#AdditionExpr (#IdentifierExpr x) (#ParenthesizedExpr (#Int64LiteralExpr 2))))) \
+               (#QualificationExpr (#IdentifierExpr c) \
+                 (#AdditionExpr (#FunctionCallExpr (#IdentifierExpr f) (#FunctionArgumentsExpr (#Int64LiteralExpr 9))) \
+                   (#IdentifierExpr y))))",
+            to_compact_s_expression(&folded, source_code)
+        );
+    }
+
+    #[test]
+    fn test_identity_folder() {
+        struct Identity;
+        impl Folder for Identity {}
+
+        let source_code = "x: Int64 = 'a' when not b | [1.5, true, ()] where {z: q?}";
+        let scan_result = scanner::scan(source_code);
+        let expression = parser::parse_expression(&scan_result);
+        let expected = to_compact_s_expression(&expression.model(), source_code);
+
+        let folded = Identity.fold_expr(expression.model());
+
+        assert_eq!(expected, to_compact_s_expression(&folded, source_code));
+    }
+}
+
+//=====================================================================================================================
@@ -1,3 +1,8 @@
-
+pub mod concrete_syntax;
 pub mod expressions;
+pub mod expressions_serde;
+pub mod folder;
 pub mod parser;
+pub mod s_expressions;
+pub mod syntax_tree;
+pub mod visitor;
@@ -6,18 +6,37 @@
 use std::collections::HashMap;
 use shared_vector::{SharedVector, Vector};
 use crate::lligne::code::scanning::{scanner, token_types};
+use crate::lligne::code::scanning::numeric_literals::{
+    NumericLiteralError, parse_floating_point_literal, parse_integer_literal,
+};
 use crate::lligne::code::parsing::expressions::Expr;
 use crate::lligne::code::parsing::expressions::StringDelimiters;
-use crate::lligne::code::scanning::tokens::Token;
-use crate::lligne::code::util::source_pos;
-use crate::lligne::code::util::source_pos::new_source_pos;
+use crate::lligne::code::parsing::syntax_tree::{new_syntax_tree, NodeId, NodeKind, SyntaxTree};
+use crate::lligne::code::scanning::string_literals::{EscapeSequence, read_escape_sequence};
+use crate::lligne::code::scanning::token_types::text_of_token_type;
+use crate::lligne::code::scanning::tokens::{LongTokenLength, Token};
+use crate::lligne::code::util::diagnostics;
+use crate::lligne::code::util::diagnostics::{Diagnostic, DiagnosticCode};
+use crate::lligne::code::util::source_files::FileId;
+use crate::lligne::code::util::source_pos::{new_source_pos, SourcePos};
 
 //=====================================================================================================================
 
 pub struct Outcome<'a> {
+    pub file_id: FileId,
     pub source_code: &'a str,
     pub new_line_offsets: SharedVector<u32>,
-    pub model: Box<Expr>,
+    pub syntax_tree: SyntaxTree,
+    pub diagnostics: SharedVector<Diagnostic>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl<'a> Outcome<'a> {
+    // Builds the boxed expression tree of the parsed syntax tree.
+    pub fn model(&self) -> Box<Expr> {
+        return self.syntax_tree.to_expr(self.syntax_tree.root());
+    }
 }
 
 //=====================================================================================================================
@@ -25,32 +44,47 @@ pub struct Outcome<'a> {
 pub fn parse_expression<'a>(scan_outcome: &'a scanner::Outcome) -> Outcome<'a> {
     let mut parser = LligneParser::new(scan_outcome);
 
-    let model = parser.parse_expr_binding_power(0);
+    parser.parse_expr_binding_power(0);
+
+    parser.parse_end_of_input();
 
     return Outcome {
+        file_id: scan_outcome.file_id,
         source_code: scan_outcome.source_code,
         new_line_offsets: scan_outcome.new_line_offsets.new_ref(),
-        model,
+        syntax_tree: parser.syntax_tree,
+        diagnostics: parser.diagnostics.into_shared(),
     };
 }
 
 //---------------------------------------------------------------------------------------------------------------------
 
-// TODO: ParseTopLevel
-// ParseParenthesizedItems parses a non-empty sequence of code expected to be the items within a record literal, e.g.
-// the top level of a file.
-//func ParseParenthesizedItems(source_code string, tokens []scanning.Token) IExpression {
-//	parser := newParser(source_code, tokens)
-//
-//	return parser.parse_parenthesized_expression(tokens[0], scanning.TokenTypeEof)
-//}
+// Parses a whole source file as the items of an implicit record that ends at the end of file instead of a brace.
+pub fn parse_source_file<'a>(scan_outcome: &'a scanner::Outcome) -> Outcome<'a> {
+    let mut parser = LligneParser::new(scan_outcome);
+
+    parser.parse_source_file_expression();
+
+    return Outcome {
+        file_id: scan_outcome.file_id,
+        source_code: scan_outcome.source_code,
+        new_line_offsets: scan_outcome.new_line_offsets.new_ref(),
+        syntax_tree: parser.syntax_tree,
+        diagnostics: parser.diagnostics.into_shared(),
+    };
+}
 
 //=====================================================================================================================
 
 struct LligneParser<'a> {
+    file_id: FileId,
     source_code: &'a str,
     tokens: SharedVector<Token>,
+    long_token_lengths: SharedVector<LongTokenLength>,
     index: usize,
+    diagnostics: Vector<Diagnostic>,
+    closing_token_types: Vector<u16>,
+    syntax_tree: SyntaxTree,
 }
 
 //---------------------------------------------------------------------------------------------------------------------
@@ -60,15 +94,157 @@ impl<'a> LligneParser<'a> {
 
     fn new(scan_outcome: &'a scanner::Outcome) -> Self {
         return LligneParser {
+            file_id: scan_outcome.file_id,
             source_code: scan_outcome.source_code,
             tokens: scan_outcome.tokens.new_ref(),
+            long_token_lengths: scan_outcome.long_token_lengths.new_ref(),
             index: 0,
+            diagnostics: Vector::new(),
+            closing_token_types: Vector::new(),
+            syntax_tree: new_syntax_tree(),
         };
     }
 
     //---------------------------------------------------------------------------------------------------------------------
 
-    fn parse_expr_binding_power(&mut self, min_binding_power: usize) -> Box<Expr> {
+    // Returns the source position of a token, including one too long to hold its own length.
+    fn source_pos(&self, token: Token) -> SourcePos {
+        return new_source_pos(token, &self.long_token_lengths);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Returns the source position of a node already added to the syntax tree.
+    fn node_pos(&self, node_id: NodeId) -> SourcePos {
+        return self.syntax_tree.source_position(node_id);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Adds a node to the syntax tree after its children.
+    fn node(&mut self, kind: NodeKind, source_position: SourcePos, children: &[NodeId]) -> NodeId {
+        return self.syntax_tree.add_node(kind, source_position, children);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Describes a token for use in a diagnostic message.
+    fn describe_token(&self, token: Token) -> String {
+        if token.token_type == token_types::EOF {
+            return "end of file".to_string();
+        }
+
+        if token.source_length == 0 {
+            return text_of_token_type(token.token_type).to_string();
+        }
+
+        return format!("'{}'", self.source_pos(token).get_text(self.source_code));
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Builds an error node for a token that has already been consumed and records the corresponding diagnostic.
+    fn error_expr(&mut self, token: Token, code: DiagnosticCode, message: &str) -> NodeId {
+        let source_position = self.source_pos(token);
+        self.report_error(source_position, code, message.to_string());
+        return self.node(NodeKind::Error, source_position, &[]);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Reports an error with a help note for the given token and builds an error node in its place.
+    fn error_expr_with_help(&mut self, token: Token, code: DiagnosticCode, message: &str, help: &str) -> NodeId {
+        let source_position = self.source_pos(token);
+        self.report(
+            diagnostics::new_error(self.file_id, source_position, code, message.to_string()).with_help(help)
+        );
+        return self.node(NodeKind::Error, source_position, &[]);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Consumes the expected closing token or else reports its absence (labeling the opening token it should match),
+    // leaving the unexpected token in place. Returns the position that ends the enclosing expression.
+    fn parse_closing_token(
+        &mut self,
+        opening_token: Token,
+        token_type: u16,
+        code: DiagnosticCode,
+        fallback_end: SourcePos,
+    ) -> SourcePos {
+        let token = self.tokens[self.index];
+
+        if token.token_type == token_type {
+            self.index += 1;
+            return self.source_pos(token);
+        }
+
+        let message = format!(
+            "Expected {} but found {}.",
+            describe_token_type(token_type),
+            self.describe_token(token)
+        );
+        self.report(self.unclosed_error(token, Some(opening_token), code, message));
+
+        return fallback_end;
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Builds an error for an unexpected token where a closing token was awaited, labeling the opening token if any.
+    fn unclosed_error(
+        &self,
+        token: Token,
+        opening_token: Option<Token>,
+        code: DiagnosticCode,
+        message: String,
+    ) -> Diagnostic {
+        let diagnostic = diagnostics::new_error(self.file_id, self.source_pos(token), code, message);
+
+        return match opening_token {
+            Some(opening_token) => {
+                let label = format!("to match this {}", self.describe_token(opening_token));
+                diagnostic.with_label(self.source_pos(opening_token), &label)
+            }
+            None => diagnostic,
+        };
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Reports any tokens left over after the expression has been parsed.
+    fn parse_end_of_input(&mut self) {
+        let token = self.tokens[self.index];
+
+        if token.token_type != token_types::EOF {
+            let message = format!("Expected end of input but found {}.", self.describe_token(token));
+            self.report_error(self.source_pos(token), DiagnosticCode::ExpectedEndOfInput, message);
+        }
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Records a diagnostic unless one has already been reported at the same position (a cascading error).
+    fn report(&mut self, diagnostic: Diagnostic) {
+        if let Some(prior) = self.diagnostics.last() {
+            if prior.source_position.start_offset == diagnostic.source_position.start_offset {
+                return;
+            }
+        }
+
+        self.diagnostics.push(diagnostic);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Records an error diagnostic unless it is a cascading error.
+    fn report_error(&mut self, source_position: SourcePos, code: DiagnosticCode, message: String) {
+        self.report(diagnostics::new_error(self.file_id, source_position, code, message));
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    fn parse_expr_binding_power(&mut self, min_binding_power: usize) -> NodeId {
         let mut lhs = self.parse_left_hand_side();
 
         loop {
@@ -119,29 +295,83 @@ impl<'a> LligneParser<'a> {
     fn parse_function_arguments_expression(
         &mut self,
         token: Token,
-    ) -> Box<Expr> {
-        let mut items: Vector<Box<Expr>> = Vector::new();
+    ) -> NodeId {
+        let mut items: Vec<NodeId> = vec![];
+
+        self.parse_items(
+            Some(token),
+            token_types::RIGHT_PARENTHESIS,
+            DiagnosticCode::ExpectedRightParenthesis,
+            &mut items,
+        );
+
+        let fallback_end = items.last().map_or(self.source_pos(token), |&item| self.node_pos(item));
+        let end_source_pos = self.parse_closing_token(
+            token,
+            token_types::RIGHT_PARENTHESIS,
+            DiagnosticCode::ExpectedRightParenthesis,
+            fallback_end,
+        );
+
+        return self.node(NodeKind::FunctionArguments, self.source_pos(token).thru(end_source_pos), &items);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Parses element access, e.g. "xs[0]" or "m["key"]", or a slice, e.g. "xs[1..3]", after the left hand side and the
+    // opening bracket have been consumed.
+    fn parse_index_expression(&mut self, lhs: NodeId) -> NodeId {
+        let opening_token = self.tokens[self.index - 1];
+
+        self.closing_token_types.push(token_types::RIGHT_BRACKET);
+        let index = self.parse_expr_binding_power(0);
+        self.closing_token_types.pop();
+
+        let end_source_pos = self.parse_closing_token(
+            opening_token,
+            token_types::RIGHT_BRACKET,
+            DiagnosticCode::ExpectedRightBracket,
+            self.node_pos(index),
+        );
+        let source_position = self.node_pos(lhs).thru(end_source_pos);
+
+        if *self.syntax_tree.kind(index) == NodeKind::Range {
+            let bounds = self.syntax_tree.remove_last_node(index);
+            return self.node(NodeKind::Slice, source_position, &[lhs, bounds[0], bounds[1]]);
+        }
+
+        return self.node(NodeKind::Index, source_position, &[lhs, index]);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Parses a comma-separated sequence of expressions up to but not including the given closing token. After a
+    // malformed item, skips ahead to the next separator so that one bad item does not hide the ones following it.
+    fn parse_items(
+        &mut self,
+        opening_token: Option<Token>,
+        closing_token_type: u16,
+        code: DiagnosticCode,
+        items: &mut Vec<NodeId>,
+    ) {
+        self.closing_token_types.push(closing_token_type);
+
+        while self.tokens[self.index].token_type != closing_token_type {
+            let diagnostics_count = self.diagnostics.len();
 
-        while self.tokens[self.index].token_type != token_types::RIGHT_PARENTHESIS {
             // Parse one expression.
             items.push(self.parse_expr_binding_power(0));
 
-            if self.tokens[self.index].token_type != token_types::COMMA {
+            let token_type = self.tokens[self.index].token_type;
+            if token_type == token_types::COMMA {
+                self.index += 1;
+            } else if token_type == closing_token_type ||
+                !self.synchronize(opening_token, closing_token_type, code, diagnostics_count) {
                 break;
             }
-            self.index += 1
         }
 
-        if self.tokens[self.index].token_type != token_types::RIGHT_PARENTHESIS {
-            panic!("Expected right parenthesis.")
-        }
-        let end_source_pos = new_source_pos(self.tokens[self.index]);
-        self.index += 1;
-
-        return Box::new(Expr::FunctionArguments {
-            source_position: new_source_pos(token).thru(end_source_pos),
-            items: items.into_shared(),
-        });
+        self.closing_token_types.pop();
     }
 
     //---------------------------------------------------------------------------------------------------------------------
@@ -151,274 +381,99 @@ impl<'a> LligneParser<'a> {
         &mut self,
         op_token: Token,
         binding_power: &BinaryBindingPower,
-        lhs: Box<Expr>,
-    ) -> Box<Expr> {
+        lhs: NodeId,
+    ) -> NodeId {
         let rhs = self.parse_expr_binding_power(binding_power.right);
 
-        match op_token.token_type {
-            token_types::AMPERSAND =>
-                Box::new(Expr::Intersect {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::AMPERSAND_AMPERSAND =>
-                Box::new(Expr::IntersectLowPrecedence {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::AND =>
-                Box::new(Expr::LogicalAnd {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::ASTERISK =>
-                Box::new(Expr::Multiplication {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::COLON =>
-                Box::new(Expr::Qualify {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
+        let kind = match op_token.token_type {
+            token_types::AMPERSAND => NodeKind::Intersect,
+            token_types::AMPERSAND_AMPERSAND => NodeKind::IntersectLowPrecedence,
+            token_types::AND => NodeKind::LogicalAnd,
+            token_types::AS => NodeKind::As,
+            token_types::ASTERISK => NodeKind::Multiplication,
+            token_types::COLON => NodeKind::Qualify,
+            token_types::DASH => NodeKind::Subtraction,
+            token_types::DOT => NodeKind::FieldReference,
+            token_types::DOT_DOT => NodeKind::Range,
+            token_types::EQUALS => NodeKind::IntersectAssignValue,
+            token_types::EQUALS_EQUALS => NodeKind::Equals,
+            token_types::EQUALS_TILDE => NodeKind::Match,
+            token_types::EXCLAMATION_EQUALS => NodeKind::NotEquals,
+            token_types::EXCLAMATION_TILDE => NodeKind::NotMatch,
+            token_types::GREATER_THAN => NodeKind::GreaterThan,
+            token_types::GREATER_THAN_OR_EQUALS => NodeKind::GreaterThanOrEquals,
+            token_types::IN => NodeKind::In,
+            token_types::IS => NodeKind::Is,
+            token_types::LESS_THAN => NodeKind::LessThan,
+            token_types::LESS_THAN_OR_EQUALS => NodeKind::LessThanOrEquals,
+            token_types::OR => NodeKind::LogicalOr,
+            token_types::PLUS => NodeKind::Addition,
+            token_types::QUESTION_COLON => NodeKind::IntersectDefaultValue,
+            token_types::RIGHT_ARROW => NodeKind::FunctionArrow,
+            token_types::SLASH => NodeKind::Division,
+            token_types::SYNTH_DOCUMENT => NodeKind::Document,
+            token_types::VERTICAL_BAR => NodeKind::Union,
+            token_types::WHEN => NodeKind::When,
+            token_types::WHERE => NodeKind::Where,
+            _ => panic!("Missing case in parse_infix_operation: {}.", op_token.token_type)
+        };
 
-            token_types::DASH =>
-                Box::new(Expr::Subtraction {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::DOT =>
-                Box::new(Expr::FieldReference {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    parent: lhs,
-                    child: rhs,
-                }),
-
-            token_types::DOT_DOT =>
-                Box::new(Expr::Range {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    first: lhs,
-                    last: rhs,
-                }),
-
-            token_types::EQUALS =>
-                Box::new(Expr::IntersectAssignValue {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::EQUALS_EQUALS =>
-                Box::new(Expr::Equals {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::EQUALS_TILDE =>
-                Box::new(Expr::Match {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::EXCLAMATION_EQUALS =>
-                Box::new(Expr::NotEquals {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::EXCLAMATION_TILDE =>
-                Box::new(Expr::NotMatch {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::GREATER_THAN =>
-                Box::new(Expr::GreaterThan {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::GREATER_THAN_OR_EQUALS =>
-                Box::new(Expr::GreaterThanOrEquals {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::IN =>
-                Box::new(Expr::In {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::IS =>
-                Box::new(Expr::Is {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::LESS_THAN =>
-                Box::new(Expr::LessThan {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::LESS_THAN_OR_EQUALS =>
-                Box::new(Expr::LessThanOrEquals {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::OR =>
-                Box::new(Expr::LogicalOr {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::PLUS =>
-                Box::new(Expr::Addition {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::QUESTION_COLON =>
-                Box::new(Expr::IntersectDefaultValue {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::RIGHT_ARROW =>
-                Box::new(Expr::FunctionArrow {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    argument: lhs,
-                    result: rhs,
-                }),
-
-            token_types::SLASH =>
-                Box::new(Expr::Division {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::SYNTH_DOCUMENT =>
-                Box::new(Expr::Document {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::VERTICAL_BAR =>
-                Box::new(Expr::Union {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::WHEN =>
-                Box::new(Expr::When {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            token_types::WHERE =>
-                Box::new(Expr::Where {
-                    source_position: lhs.get_source_position().thru(rhs.get_source_position()),
-                    lhs,
-                    rhs,
-                }),
-
-            _ =>
-                panic!("Missing case in parse_infix_operation: {}.", op_token.token_type)
-        }
+        let source_position = self.node_pos(lhs).thru(self.node_pos(rhs));
+        return self.node(kind, source_position, &[lhs, rhs]);
     }
 
     //---------------------------------------------------------------------------------------------------------------------
 
-    fn parse_left_hand_side(&mut self) -> Box<Expr> {
+    fn parse_left_hand_side(&mut self) -> NodeId {
         let token = self.tokens[self.index];
         self.index += 1;
 
         return match token.token_type {
             token_types::BACK_TICKED_STRING =>
-                Box::new(Expr::StringLiteral {
-                    source_position: source_pos::new_source_pos(token),
-                    delimiters: StringDelimiters::BackTicksMultiline,
-                }),
+                self.string_literal_expr(token, StringDelimiters::BackTicksMultiline),
 
             token_types::BUILT_IN_TYPE =>
-                Box::new(Expr::BuiltInType {
-                    source_position: source_pos::new_source_pos(token),
-                }),
+                self.node(NodeKind::BuiltInType, self.source_pos(token), &[]),
 
             token_types::DASH =>
                 self.parse_negation_operation_expression(token),
 
             token_types::DOUBLE_QUOTED_STRING =>
-                Box::new(Expr::StringLiteral {
-                    source_position: source_pos::new_source_pos(token),
-                    delimiters: StringDelimiters::DoubleQuotes,
-                }),
+                self.string_literal_expr(token, StringDelimiters::DoubleQuotes),
 
             token_types::FALSE =>
-                Box::new(Expr::BooleanLiteral {
-                    source_position: source_pos::new_source_pos(token),
-                    value: false,
-                }),
+                self.node(NodeKind::BooleanLiteral(false), self.source_pos(token), &[]),
 
             token_types::FLOATING_POINT_LITERAL => {
-                let source_position = source_pos::new_source_pos(token);
+                let source_position = self.source_pos(token);
                 let value_str = source_position.get_text(self.source_code);
-                let value: f64 = value_str.parse().unwrap();
-                return Box::new(Expr::Float64Literal {
-                    source_position: source_position,
-                    value: value,
-                });
+                return match parse_floating_point_literal(value_str) {
+                    Ok(value) => self.node(NodeKind::Float64Literal(value), source_position, &[]),
+                    Err(NumericLiteralError::Malformed) => self.error_expr(
+                        token,
+                        DiagnosticCode::InvalidFloatingPointLiteral,
+                        "Invalid floating point literal.",
+                    ),
+                    Err(NumericLiteralError::OutOfRange) => self.error_expr_with_help(
+                        token,
+                        DiagnosticCode::FloatingPointLiteralOutOfRange,
+                        "Floating point literal is too large for a 64-bit float.",
+                        "The largest floating point literal is about 1.7976931348623157e308.",
+                    ),
+                };
             }
 
             token_types::IDENTIFIER =>
-                Box::new(Expr::Identifier {
-                    source_position: source_pos::new_source_pos(token),
-                }),
+                self.node(NodeKind::Identifier, self.source_pos(token), &[]),
 
-            token_types::INTEGER_LITERAL => {
-                let source_position = source_pos::new_source_pos(token);
-                let value_str = source_position.get_text(self.source_code);
-                let value: i64 = value_str.parse().unwrap();
-                return Box::new(Expr::Int64Literal {
-                    source_position: source_pos::new_source_pos(token),
-                    value: value,
-                });
-            }
+            token_types::INTEGER_LITERAL =>
+                self.integer_literal_expr(self.source_pos(token)),
+
+            token_types::INVALID_STRING_LITERAL =>
+                self.parse_invalid_string_literal(token),
 
             token_types::LEADING_DOCUMENTATION =>
-                Box::new(Expr::LeadingDocumentation {
-                    source_position: source_pos::new_source_pos(token),
-                }),
+                self.node(NodeKind::LeadingDocumentation, self.source_pos(token), &[]),
 
             token_types::LEFT_BRACE =>
                 self.parse_record_expression(token),
@@ -433,65 +488,97 @@ impl<'a> LligneParser<'a> {
                 self.parse_logical_not_operation_expression(token),
 
             token_types::SINGLE_QUOTED_STRING =>
-                Box::new(Expr::StringLiteral {
-                    source_position: source_pos::new_source_pos(token),
-                    delimiters: StringDelimiters::SingleQuotes,
-                }),
+                self.string_literal_expr(token, StringDelimiters::SingleQuotes),
 
             token_types::TRAILING_DOCUMENTATION =>
-                Box::new(Expr::TrailingDocumentation {
-                    source_position: source_pos::new_source_pos(token),
-                }),
+                self.node(NodeKind::TrailingDocumentation, self.source_pos(token), &[]),
 
             token_types::TRUE =>
-                Box::new(Expr::BooleanLiteral {
-                    source_position: source_pos::new_source_pos(token),
-                    value: true,
-                }),
-
-            //	default:
-            //	this.expectedType(
-            //	LlaceTokenType.CHAR_LITERAL,
-            //	LlaceTokenType.DASH,
-            //	LlaceTokenType.IDENTIFIER,
-            //	LlaceTokenType.INTEGER_LITERAL,
-            //	LlaceTokenType.STRING_LITERAL
-            //	)
-
-            _ => panic!("Unfinished parsing code: {}.'", token.token_type)
+                self.node(NodeKind::BooleanLiteral(true), self.source_pos(token), &[]),
+
+            token_types::UNCLOSED_DOUBLE_QUOTED_STRING =>
+                self.error_expr_with_help(
+                    token,
+                    DiagnosticCode::UnclosedStringLiteral,
+                    "String literal extends past end of line.",
+                    "For text spanning lines, use a back-ticked string block with a back tick opening each line.",
+                ),
+
+            token_types::UNCLOSED_SINGLE_QUOTED_STRING =>
+                self.error_expr(token, DiagnosticCode::UnclosedCharacterLiteral, "Character literal extends past end of line."),
+
+            token_types::UNRECOGNIZED_CHAR =>
+                self.error_expr(token, DiagnosticCode::UnrecognizedCharacter, "Unrecognized character."),
+
+            _ => self.parse_unexpected_token(token)
         };
     }
 
     //---------------------------------------------------------------------------------------------------------------------
 
+    // Reports each invalid escape sequence within a string or character literal and builds an error node in place
+    // of the whole literal.
+    fn parse_invalid_string_literal(&mut self, token: Token) -> NodeId {
+        let source_position = self.source_pos(token);
+        let text = source_position.get_text(self.source_code);
+
+        let mut index = 0;
+        while let Some(offset) = text[index..].find('\\') {
+            index += offset;
+
+            let length = match read_escape_sequence(&text[index..]) {
+                EscapeSequence::Valid { length, .. } => length,
+                EscapeSequence::Invalid { length } => {
+                    let escape_position = SourcePos {
+                        start_offset: source_position.start_offset + index as u32,
+                        end_offset: source_position.start_offset + (index + length) as u32,
+                    };
+                    let message = format!("Invalid escape sequence '{}'.", &text[index..index + length]);
+                    self.report_error(escape_position, DiagnosticCode::InvalidEscapeSequence, message);
+                    length
+                }
+            };
+
+            index += length;
+        }
+
+        return self.node(NodeKind::Error, source_position, &[]);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
     fn parse_logical_not_operation_expression(
         &mut self,
         token: Token,
-    ) -> Box<Expr> {
+    ) -> NodeId {
         let prefix_ops = binding_powers().prefix;
         let binding_power = prefix_ops.get(&token.token_type).unwrap();
 
         let rhs = self.parse_expr_binding_power(binding_power.power);
-        return Box::new(Expr::LogicalNotOperation {
-            source_position: new_source_pos(token),
-            operand: rhs,
-        });
+        return self.node(NodeKind::LogicalNotOperation, self.source_pos(token).thru(self.node_pos(rhs)), &[rhs]);
     }
 
     //---------------------------------------------------------------------------------------------------------------------
 
+    // Parses a negation, folding a '-' directly followed by an integer literal into a negative literal (so that
+    // -9223372036854775808 can be written) unless an operator binding more tightly than the '-' follows the literal.
     fn parse_negation_operation_expression(
         &mut self,
         token: Token,
-    ) -> Box<Expr> {
+    ) -> NodeId {
         let prefix_ops = binding_powers().prefix;
         let binding_power = prefix_ops.get(&token.token_type).unwrap();
 
+        let literal_token = self.tokens[self.index];
+        if literal_token.token_type == token_types::INTEGER_LITERAL &&
+            literal_token.source_offset == token.source_offset + 1 &&
+            !binds_more_tightly(self.tokens[self.index + 1].token_type, binding_power.power) {
+            self.index += 1;
+            return self.integer_literal_expr(self.source_pos(token).thru(self.source_pos(literal_token)));
+        }
+
         let rhs = self.parse_expr_binding_power(binding_power.power);
-        return Box::new(Expr::NegationOperation {
-            source_position: new_source_pos(token).thru(rhs.get_source_position()),
-            operand: rhs,
-        });
+        return self.node(NodeKind::NegationOperation, self.source_pos(token).thru(self.node_pos(rhs)), &[rhs]);
     }
 
     //---------------------------------------------------------------------------------------------------------------------
@@ -499,83 +586,89 @@ impl<'a> LligneParser<'a> {
     fn parse_parenthesized_expression(
         &mut self,
         token: Token,
-    ) -> Box<Expr> {
+    ) -> NodeId {
 
         // Handle empty parentheses specially.
         if self.tokens[self.index].token_type == token_types::RIGHT_PARENTHESIS {
-            let end_source_pos = new_source_pos(self.tokens[self.index]);
+            let end_source_pos = self.source_pos(self.tokens[self.index]);
             self.index += 1;
 
-            return Box::new(Expr::Unit {
-                source_position: new_source_pos(token).thru(end_source_pos),
-            });
+            return self.node(NodeKind::Unit, self.source_pos(token).thru(end_source_pos), &[]);
         }
 
         // Parse one expression.
+        self.closing_token_types.push(token_types::RIGHT_PARENTHESIS);
+        let diagnostics_count = self.diagnostics.len();
         let inner = self.parse_expr_binding_power(0);
 
-        // Comma means function parameters
-        if self.tokens[self.index].token_type == token_types::COMMA {
-            self.index += 1;
-
-            let mut items: Vector<Box<Expr>> = Vector::new();
-            items.push(inner);
-
-            while self.tokens[self.index].token_type != token_types::RIGHT_PARENTHESIS {
-                // Parse one expression.
-                items.push(self.parse_expr_binding_power(0));
-
-                if self.tokens[self.index].token_type != token_types::COMMA {
-                    break;
-                }
-                self.index += 1
-            }
-
-            if self.tokens[self.index].token_type != token_types::RIGHT_PARENTHESIS {
-                panic!("Expected right parenthesis.")
+        // Comma means function parameters (also when recovery from a malformed expression stops at a separator).
+        let is_item_list = match self.tokens[self.index].token_type {
+            token_types::COMMA => {
+                self.index += 1;
+                true
             }
-            let end_source_pos = new_source_pos(self.tokens[self.index]);
-            self.index += 1;
-
-            return Box::new(Expr::FunctionArguments {
-                source_position: new_source_pos(token).thru(end_source_pos),
-                items: items.into_shared(),
-            });
+            token_types::RIGHT_PARENTHESIS => false,
+            _ => self.synchronize(
+                Some(token),
+                token_types::RIGHT_PARENTHESIS,
+                DiagnosticCode::ExpectedRightParenthesis,
+                diagnostics_count,
+            ),
+        };
+        self.closing_token_types.pop();
+
+        if is_item_list {
+            let mut items = vec![inner];
+
+            self.parse_items(
+                Some(token),
+                token_types::RIGHT_PARENTHESIS,
+                DiagnosticCode::ExpectedRightParenthesis,
+                &mut items,
+            );
+
+            let fallback_end = self.node_pos(*items.last().unwrap());
+            let end_source_pos = self.parse_closing_token(
+                token,
+                token_types::RIGHT_PARENTHESIS,
+                DiagnosticCode::ExpectedRightParenthesis,
+                fallback_end,
+            );
+
+            return self.node(NodeKind::FunctionArguments, self.source_pos(token).thru(end_source_pos), &items);
         }
 
-        if self.tokens[self.index].token_type != token_types::RIGHT_PARENTHESIS {
-            panic!("Expected right parenthesis.")
-        }
+        let end_source_pos = self.parse_closing_token(
+            token,
+            token_types::RIGHT_PARENTHESIS,
+            DiagnosticCode::ExpectedRightParenthesis,
+            self.node_pos(inner),
+        );
 
-        let end_source_pos = new_source_pos(self.tokens[self.index]);
-        self.index += 1;
-
-        return Box::new(Expr::Parenthesized {
-            source_position: new_source_pos(token).thru(end_source_pos),
-            inner_expr: inner,
-        });
+        return self.node(NodeKind::Parenthesized, self.source_pos(token).thru(end_source_pos), &[inner]);
     }
 
     //---------------------------------------------------------------------------------------------------------------------
 
-    fn parse_postfix_expression(&mut self, op_token: Token, lhs: Box<Expr>) -> Box<Expr> {
+    fn parse_postfix_expression(&mut self, op_token: Token, lhs: NodeId) -> NodeId {
         return match op_token.token_type {
             token_types::LEFT_PARENTHESIS => {
                 let args = self.parse_function_arguments_expression(op_token);
-                return Box::new(Expr::FunctionCall {
-                    source_position: lhs.get_source_position().thru(args.get_source_position()),
-                    function_reference: lhs,
-                    argument: args,
-                });
+                let source_position = self.node_pos(lhs).thru(self.node_pos(args));
+                return self.node(NodeKind::FunctionCall, source_position, &[lhs, args]);
             }
 
+            token_types::LEFT_BRACKET =>
+                self.parse_index_expression(lhs),
+
             token_types::QUESTION =>
-                Box::new(Expr::Optional {
-                    source_position: lhs.get_source_position(),
-                    operand: lhs,
-                }),
+                self.node(NodeKind::Optional, self.node_pos(lhs), &[lhs]),
 
-            _ => panic!("Unfinished postfix parsing code: '{}'.", op_token.token_type)
+            _ => {
+                let message = format!("Unsupported postfix operator {}.", self.describe_token(op_token));
+                self.report_error(self.source_pos(op_token), DiagnosticCode::UnsupportedPostfixOperator, message);
+                self.node(NodeKind::Error, self.node_pos(lhs).thru(self.source_pos(op_token)), &[])
+            }
         };
     }
 
@@ -584,71 +677,208 @@ impl<'a> LligneParser<'a> {
     fn parse_record_expression(
         &mut self,
         token: Token,
-    ) -> Box<Expr> {
-        let mut items: Vector<Box<Expr>> = Vector::new();
+    ) -> NodeId {
+        let mut items: Vec<NodeId> = vec![];
 
-        while self.tokens[self.index].token_type != token_types::RIGHT_BRACE {
-            // Parse one expression.
-            items.push(self.parse_expr_binding_power(0));
-
-            if self.tokens[self.index].token_type != token_types::COMMA {
-                break;
-            }
-            self.index += 1
-        }
+        self.parse_items(Some(token), token_types::RIGHT_BRACE, DiagnosticCode::ExpectedRightBrace, &mut items);
 
-        if self.tokens[self.index].token_type != token_types::RIGHT_BRACE {
-            panic!("Expected right brace");
-        }
-        let end_source_pos = new_source_pos(self.tokens[self.index]);
-        self.index += 1;
+        let fallback_end = items.last().map_or(self.source_pos(token), |&item| self.node_pos(item));
+        let end_source_pos = self.parse_closing_token(
+            token,
+            token_types::RIGHT_BRACE,
+            DiagnosticCode::ExpectedRightBrace,
+            fallback_end,
+        );
 
-        return Box::new(Expr::Record {
-            source_position: new_source_pos(token).thru(end_source_pos),
-            items: items.into_shared(),
-        });
+        return self.node(NodeKind::Record, self.source_pos(token).thru(end_source_pos), &items);
     }
 
     //---------------------------------------------------------------------------------------------------------------------
 
-    fn parse_sequence_literal(&mut self, token: Token) -> Box<Expr> {
-        let start_source_pos = new_source_pos(token);
-        let mut items: Vector<Box<Expr>> = Vector::new();
+    fn parse_sequence_literal(&mut self, token: Token) -> NodeId {
+        let start_source_pos = self.source_pos(token);
+        let mut items: Vec<NodeId> = vec![];
 
         if self.tokens[self.index].token_type == token_types::RIGHT_BRACKET {
-            let end_source_pos = new_source_pos(self.tokens[self.index]);
+            let end_source_pos = self.source_pos(self.tokens[self.index]);
             self.index += 1;
-            return Box::new(Expr::ArrayLiteral {
-                source_position: start_source_pos.thru(end_source_pos),
-                elements: items.into_shared(),
-            });
+            return self.node(NodeKind::ArrayLiteral, start_source_pos.thru(end_source_pos), &items);
         }
 
-        while self.tokens[self.index].token_type != token_types::RIGHT_BRACKET {
-            // Parse one expression.
-            items.push(self.parse_expr_binding_power(0));
+        self.parse_items(Some(token), token_types::RIGHT_BRACKET, DiagnosticCode::ExpectedRightBracket, &mut items);
 
-            if self.tokens[self.index].token_type != token_types::COMMA {
-                break;
+        let fallback_end = items.last().map_or(start_source_pos, |&item| self.node_pos(item));
+        let end_source_pos = self.parse_closing_token(
+            token,
+            token_types::RIGHT_BRACKET,
+            DiagnosticCode::ExpectedRightBracket,
+            fallback_end,
+        );
+
+        return self.node(NodeKind::ArrayLiteral, start_source_pos.thru(end_source_pos), &items);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    fn parse_source_file_expression(&mut self) -> NodeId {
+        let mut items: Vec<NodeId> = vec![];
+
+        self.parse_items(None, token_types::EOF, DiagnosticCode::ExpectedEndOfInput, &mut items);
+
+        let source_position = SourcePos {
+            start_offset: 0,
+            end_offset: self.tokens[self.index].source_offset,
+        };
+
+        return self.node(NodeKind::SourceFile, source_position, &items);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Recovers from a malformed item in a sequence by skipping tokens (and any bracketed groups within them) up to a
+    // synchronization point. A ',' or ';' is consumed and true returned to signal that another item follows. The
+    // expected closing token, a closing token awaited by an enclosing construct, or the end of file is left in
+    // place and false returned. An error is reported unless one was already reported for the malformed item.
+    fn synchronize(
+        &mut self,
+        opening_token: Option<Token>,
+        closing_token_type: u16,
+        code: DiagnosticCode,
+        diagnostics_count: usize,
+    ) -> bool {
+        if self.diagnostics.len() == diagnostics_count {
+            let token = self.tokens[self.index];
+            let message = format!(
+                "Expected ',' or {} but found {}.",
+                describe_token_type(closing_token_type),
+                self.describe_token(token)
+            );
+            self.report(self.unclosed_error(token, opening_token, code, message));
+        }
+
+        let mut depth = 0;
+
+        loop {
+            let token_type = self.tokens[self.index].token_type;
+
+            match token_type {
+                token_types::EOF =>
+                    return false,
+
+                token_types::COMMA | token_types::SEMICOLON if depth == 0 => {
+                    self.index += 1;
+                    return true;
+                }
+
+                token_types::LEFT_BRACE | token_types::LEFT_BRACKET | token_types::LEFT_PARENTHESIS =>
+                    depth += 1,
+
+                token_types::RIGHT_BRACE | token_types::RIGHT_BRACKET | token_types::RIGHT_PARENTHESIS => {
+                    if depth > 0 {
+                        depth -= 1;
+                    } else if token_type == closing_token_type || self.closing_token_types.contains(&token_type) {
+                        return false;
+                    }
+                }
+
+                _ => {}
             }
-            self.index += 1
+
+            self.index += 1;
         }
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Reports a token that cannot start an expression and substitutes an error node. Closing punctuation and the end
+    // of file are left in place for an enclosing construct to consume.
+    fn parse_unexpected_token(&mut self, token: Token) -> NodeId {
+        let message = format!("Expected an expression but found {}.", self.describe_token(token));
+        self.report_error(self.source_pos(token), DiagnosticCode::ExpectedExpression, message);
 
-        if self.tokens[self.index].token_type != token_types::RIGHT_BRACKET {
-            panic!("Expected right bracket")
+        if is_closing_token_type(token.token_type) {
+            self.index -= 1;
+            let source_position = SourcePos { start_offset: token.source_offset, end_offset: token.source_offset };
+            return self.node(NodeKind::Error, source_position, &[]);
         }
-        let end_source_pos = new_source_pos(self.tokens[self.index]);
-        self.index += 1;
 
-        return Box::new(Expr::ArrayLiteral {
-            source_position: start_source_pos.thru(end_source_pos),
-            elements: items.into_shared(),
-        });
+        return self.node(NodeKind::Error, self.source_pos(token), &[]);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Builds an integer literal node (possibly negative) with its value, or an error node if the value is malformed or
+    // does not fit in 64 bits.
+    fn integer_literal_expr(&mut self, source_position: SourcePos) -> NodeId {
+        let text = source_position.get_text(self.source_code);
+
+        return match parse_integer_literal(text) {
+            Ok(value) => self.node(NodeKind::Int64Literal(value), source_position, &[]),
+            Err(NumericLiteralError::Malformed) => {
+                let message = "Invalid integer literal.".to_string();
+                self.report_error(source_position, DiagnosticCode::InvalidIntegerLiteral, message);
+                self.node(NodeKind::Error, source_position, &[])
+            }
+            Err(NumericLiteralError::OutOfRange) => {
+                let (message, help) = if text.starts_with('-') {
+                    (
+                        "Integer literal is too small for a 64-bit integer.",
+                        "The smallest integer literal is -9223372036854775808 (-0x8000_0000_0000_0000).",
+                    )
+                } else {
+                    (
+                        "Integer literal is too large for a 64-bit integer.",
+                        "The largest integer literal is 9223372036854775807 (0x7FFF_FFFF_FFFF_FFFF).",
+                    )
+                };
+                let code = DiagnosticCode::IntegerLiteralOutOfRange;
+                self.report(
+                    diagnostics::new_error(self.file_id, source_position, code, message.to_string()).with_help(help)
+                );
+                self.node(NodeKind::Error, source_position, &[])
+            }
+        };
+    }
+
+    //---------------------------------------------------------------------------------------------------------------------
+
+    // Builds a string literal node with its decoded value.
+    fn string_literal_expr(&mut self, token: Token, delimiters: StringDelimiters) -> NodeId {
+        let source_position = self.source_pos(token);
+        let value = delimiters.decode(source_position.get_text(self.source_code));
+
+        return self.node(NodeKind::StringLiteral(delimiters, value), source_position, &[]);
     }
 
     //=====================================================================================================================
 }
 
+// Describes a token type for use in a diagnostic message.
+fn describe_token_type(token_type: u16) -> String {
+    if token_type == token_types::EOF {
+        return "end of file".to_string();
+    }
+
+    return format!("'{}'", text_of_token_type(token_type));
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Determines whether a token type ends an expression list or the input rather than starting an expression.
+fn is_closing_token_type(token_type: u16) -> bool {
+    return matches!(
+        token_type,
+        token_types::COMMA |
+        token_types::EOF |
+        token_types::RIGHT_BRACE |
+        token_types::RIGHT_BRACKET |
+        token_types::RIGHT_PARENTHESIS |
+        token_types::SEMICOLON
+    );
+}
+
+//=====================================================================================================================
+
 struct BinaryBindingPower {
     left: usize,
     right: usize,
@@ -746,6 +976,12 @@ fn binding_powers() -> BindingPowers {
 
     level += 2;
 
+    // Conversion binds tighter than ':', 'is', and arithmetic, but looser than negation, so "-x as Float64" converts
+    // the negated value.
+    infix_binding_powers.insert(token_types::AS, BinaryBindingPower { left: level, right: level + 1 });
+
+    level += 2;
+
     prefix_binding_powers.insert(token_types::DASH, UnaryBindingPower { power: level });
 
     level += 2;
@@ -769,131 +1005,657 @@ fn binding_powers() -> BindingPowers {
     };
 }
 
+//---------------------------------------------------------------------------------------------------------------------
+
+// Determines whether an operator binds more tightly than a prefix operator with the given binding power, so that it
+// applies to the prefix operator's operand rather than to the whole prefix operation.
+fn binds_more_tightly(token_type: u16, prefix_binding_power: usize) -> bool {
+    let binding_powers = binding_powers();
+
+    return binding_powers.postfix.get(&token_type).is_some_and(|bp| bp.power >= prefix_binding_power) ||
+        binding_powers.infix.get(&token_type).is_some_and(|bp| bp.left >= prefix_binding_power);
+}
+
 //=====================================================================================================================
 
 #[cfg(test)]
 mod tests {
+    use crate::lligne::code::parsing::s_expressions::to_compact_s_expression;
     use crate::lligne::code::scanning::token_filters::leading_trailing_documentation;
+    use crate::lligne::code::util::source_files::new_source_database;
     use super::*;
 
-    fn check(source_code: &str) {
+    fn check(source_code: &str, expected_tree: &str) {
         let mut scan_result = scanner::scan(source_code);
 
         scan_result = leading_trailing_documentation::filter(scan_result);
 
         let expression = parse_expression(&scan_result);
 
-        assert!(expression.model.get_source_position().start_offset <
-            expression.model.get_source_position().end_offset)
+        assert!(expression.diagnostics.is_empty(), "Unexpected diagnostics for '{}'", source_code);
+        assert_eq!(
+            expected_tree,
+            to_compact_s_expression(&expression.model(), source_code),
+            "Wrong tree for '{}'",
+            source_code
+        );
+    }
+
+    fn check_errors(source_code: &str, expected_diagnostics: &[(DiagnosticCode, u32, u32)]) {
+        let mut scan_result = scanner::scan(source_code);
+
+        scan_result = leading_trailing_documentation::filter(scan_result);
+
+        let expression = parse_expression(&scan_result);
+
+        let actual_diagnostics: Vec<(DiagnosticCode, u32, u32)> = expression.diagnostics.iter()
+            .map(|d| (d.code, d.source_position.start_offset, d.source_position.end_offset))
+            .collect();
+        assert_eq!(expected_diagnostics, actual_diagnostics.as_slice(), "Wrong diagnostics for '{}'", source_code);
     }
 
     #[test]
     fn test_identifier_literals() {
-        check("abc");
-        check("\n  d  \n");
+        check("abc", "(#IdentifierExpr abc)");
+        check("\n  d  \n", "(#IdentifierExpr d)");
     }
 
     #[test]
     fn test_integer_literals() {
-        check("123");
-        check("789");
+        check("123", "(#Int64LiteralExpr 123)");
+        check("789", "(#Int64LiteralExpr 789)");
     }
 
     #[test]
     fn test_floating_point_literals() {
-        check("1.23");
-        check("78.9");
+        check("1.23", "(#Float64LiteralExpr 1.23)");
+        check("78.9", "(#Float64LiteralExpr 78.9)");
     }
 
     #[test]
     fn test_multiline_string_literals() {
-        check("` line one\n ` line two\n");
+        check("` line one\n ` line two\n", "(#BackTickedStringBlockExpr  line one\\n line two)");
     }
 
     #[test]
     fn test_string_literals() {
-        check(r#""123""#);
-        check(r#"'789'"#);
+        check(r#""123""#, "(#DoubleQuotedStringExpr \"123\")");
+        check(r#"'789'"#, "(#SingleQuotedStringExpr '789')");
     }
 
     #[test]
     fn test_leading_documentation() {
-        check("// line one\n // line two\nq");
+        check(
+            "// line one\n // line two\nq",
+            "(#DocumentExpr (#LeadingDocumentationExpr // line one\\n // line two\\n) (#IdentifierExpr q))",
+        );
     }
 
     #[test]
     fn test_trailing_documentation() {
-        check("q // line one\n // line two\n")
+        check(
+            "q // line one\n // line two\n",
+            "(#DocumentExpr (#IdentifierExpr q) (#TrailingDocumentationExpr // line one\\n // line two\\n))",
+        )
     }
 
     #[test]
     fn test_addition() {
-        check("x + 1");
-        check(" 3 + y");
-        check("x + 1.7");
-        check(" 3.666 + y");
+        check("x + 1", "(#AdditionExpr (#IdentifierExpr x) (#Int64LiteralExpr 1))");
+        check(" 3 + y", "(#AdditionExpr (#Int64LiteralExpr 3) (#IdentifierExpr y))");
+        check("x + 1.7", "(#AdditionExpr (#IdentifierExpr x) (#Float64LiteralExpr 1.7))");
+        check(" 3.666 + y", "(#AdditionExpr (#Float64LiteralExpr 3.666) (#IdentifierExpr y))");
     }
 
     #[test]
     fn test_built_in_types() {
-        check("x: Int64");
-        check("isWorking: Bool");
-        check("amount: Float64");
-        check("name: String");
+        check("x: Int64", "(#QualificationExpr (#IdentifierExpr x) (#BuiltInTypeExpr Int64))");
+        check("isWorking: Bool", "(#QualificationExpr (#IdentifierExpr isWorking) (#BuiltInTypeExpr Bool))");
+        check("amount: Float64", "(#QualificationExpr (#IdentifierExpr amount) (#BuiltInTypeExpr Float64))");
+        check("name: String", "(#QualificationExpr (#IdentifierExpr name) (#BuiltInTypeExpr String))");
     }
 
     #[test]
     fn test_table_of_expressions() {
-        let tests: Vec<&str> = vec![
-            "x + 1",
-            "q - 4",
-            "a - b + 3",
-            "a + b + 3",
-            "1 * 2",
-            "x + 3 * g",
-            "a + b / 2 - c",
-            "-a",
-            "-2 * a - b * -r",
-            "a.b.c",
-            "x.y + z.q",
-            "\"s\"",
-            "\"string tied in a knot\"",
-            "'c'",
-            "(x + 5)",
-            "((x + 5) / 3)",
-            "()",
-            "{}",
-            "{x: int && 5}",
-            "{x: int && 5, y: string && \"s\"}",
-            "{x: int ?: 5, y: string ?: \"s\"}",
-            "[]",
-            "[1, 2, 3, 4, 5]",
-            "true and false",
-            "a and b",
-            "a and b or c",
-            "a and not b",
-            "not a or b",
-            "1 == 2",
-            "1 + 1 == 2 / 1",
-            "1 + 1 < 2 / 1",
-            "1 + 1 <= 2 / 1",
-            "1 + 1 >= 2 / 1",
-            "x =~ y",
-            "x !~ y",
-            "int?",
-            "float | int?",
-            "float & 7.0",
-            "f(x: 0)",
-            "(a: f(x: 0))",
-            "1..9",
-            "x in 1..9",
-            "x is Widget",
-            "1 when n == 0\n| n * f(n - 1) when n > 0",
-            "f: (n: int) -> int = 1 when n == 0\n| n * f(n-1) when n > 0",
-            "x = y + z where {y: 3, z: 5}",
+        let tests: Vec<(&str, &str)> = vec![
+            ("x + 1", "(#AdditionExpr (#IdentifierExpr x) (#Int64LiteralExpr 1))"),
+            ("q - 4", "(#SubtractionExpr (#IdentifierExpr q) (#Int64LiteralExpr 4))"),
+            (
+                "a - b + 3",
+                "(#AdditionExpr (#SubtractionExpr (#IdentifierExpr a) (#IdentifierExpr b)) (#Int64LiteralExpr 3))",
+            ),
+            (
+                "a + b + 3",
+                "(#AdditionExpr (#AdditionExpr (#IdentifierExpr a) (#IdentifierExpr b)) (#Int64LiteralExpr 3))",
+            ),
+            ("1 * 2", "(#MultiplicationExpr (#Int64LiteralExpr 1) (#Int64LiteralExpr 2))"),
+            (
+                "x + 3 * g",
+                "(#AdditionExpr (#IdentifierExpr x) (#MultiplicationExpr (#Int64LiteralExpr 3) (#IdentifierExpr g)))",
+            ),
+            (
+                "a + b / 2 - c",
+                "(#SubtractionExpr (#AdditionExpr (#IdentifierExpr a) (#DivisionExpr (#IdentifierExpr b) (#Int64LiteralExpr 2))) (#IdentifierExpr c))",
+            ),
+            ("-a", "(#NegationExpr (#IdentifierExpr a))"),
+            (
+                "-2 * a - b * -r",
+                "(#SubtractionExpr (#MultiplicationExpr (#Int64LiteralExpr -2) (#IdentifierExpr a)) (#MultiplicationExpr (#IdentifierExpr b) (#NegationExpr (#IdentifierExpr r))))",
+            ),
+            ("- 2", "(#NegationExpr (#Int64LiteralExpr 2))"),
+            ("-2[0]", "(#NegationExpr (#IndexExpr (#Int64LiteralExpr 2) (#Int64LiteralExpr 0)))"),
+            ("a - -0x10", "(#SubtractionExpr (#IdentifierExpr a) (#Int64LiteralExpr -16))"),
+            (
+                "a.b.c",
+                "(#FieldReferenceExpr (#FieldReferenceExpr (#IdentifierExpr a) (#IdentifierExpr b)) (#IdentifierExpr c))",
+            ),
+            (
+                "x.y + z.q",
+                "(#AdditionExpr (#FieldReferenceExpr (#IdentifierExpr x) (#IdentifierExpr y)) (#FieldReferenceExpr (#IdentifierExpr z) (#IdentifierExpr q)))",
+            ),
+            ("\"s\"", "(#DoubleQuotedStringExpr \"s\")"),
+            ("\"string tied in a knot\"", "(#DoubleQuotedStringExpr \"string tied in a knot\")"),
+            ("'c'", "(#SingleQuotedStringExpr 'c')"),
+            ("(x + 5)", "(#ParenthesizedExpr (#AdditionExpr (#IdentifierExpr x) (#Int64LiteralExpr 5)))"),
+            (
+                "((x + 5) / 3)",
+                "(#ParenthesizedExpr (#DivisionExpr (#ParenthesizedExpr (#AdditionExpr (#IdentifierExpr x) (#Int64LiteralExpr 5))) (#Int64LiteralExpr 3)))",
+            ),
+            ("()", "(#UnitExpr)"),
+            ("{}", "(#RecordExpr)"),
+            (
+                "{x: int && 5}",
+                "(#RecordExpr (#QualificationExpr (#IdentifierExpr x) (#IntersectLowPrecedenceExpr (#IdentifierExpr int) (#Int64LiteralExpr 5))))",
+            ),
+            (
+                "{x: int && 5, y: string && \"s\"}",
+                "(#RecordExpr (#QualificationExpr (#IdentifierExpr x) (#IntersectLowPrecedenceExpr (#IdentifierExpr int) (#Int64LiteralExpr 5))) (#QualificationExpr (#IdentifierExpr y) (#IntersectLowPrecedenceExpr (#IdentifierExpr string) (#DoubleQuotedStringExpr \"s\"))))",
+            ),
+            (
+                "{x: int ?: 5, y: string ?: \"s\"}",
+                "(#RecordExpr (#IntersectDefaultValueExpr (#QualificationExpr (#IdentifierExpr x) (#IdentifierExpr int)) (#Int64LiteralExpr 5)) (#IntersectDefaultValueExpr (#QualificationExpr (#IdentifierExpr y) (#IdentifierExpr string)) (#DoubleQuotedStringExpr \"s\")))",
+            ),
+            ("[]", "(#ArrayLiteralExpr)"),
+            (
+                "[1, 2, 3, 4, 5]",
+                "(#ArrayLiteralExpr (#Int64LiteralExpr 1) (#Int64LiteralExpr 2) (#Int64LiteralExpr 3) (#Int64LiteralExpr 4) (#Int64LiteralExpr 5))",
+            ),
+            ("true and false", "(#LogicalAndExpr (#BooleanLiteralExpr true) (#BooleanLiteralExpr false))"),
+            ("a and b", "(#LogicalAndExpr (#IdentifierExpr a) (#IdentifierExpr b))"),
+            (
+                "a and b or c",
+                "(#LogicalOrExpr (#LogicalAndExpr (#IdentifierExpr a) (#IdentifierExpr b)) (#IdentifierExpr c))",
+            ),
+            ("a and not b", "(#LogicalAndExpr (#IdentifierExpr a) (#LogicalNotExpr (#IdentifierExpr b)))"),
+            ("not a or b", "(#LogicalOrExpr (#LogicalNotExpr (#IdentifierExpr a)) (#IdentifierExpr b))"),
+            ("1 == 2", "(#EqualsExpr (#Int64LiteralExpr 1) (#Int64LiteralExpr 2))"),
+            (
+                "1 + 1 == 2 / 1",
+                "(#EqualsExpr (#AdditionExpr (#Int64LiteralExpr 1) (#Int64LiteralExpr 1)) (#DivisionExpr (#Int64LiteralExpr 2) (#Int64LiteralExpr 1)))",
+            ),
+            (
+                "1 + 1 < 2 / 1",
+                "(#LessThanExpr (#AdditionExpr (#Int64LiteralExpr 1) (#Int64LiteralExpr 1)) (#DivisionExpr (#Int64LiteralExpr 2) (#Int64LiteralExpr 1)))",
+            ),
+            (
+                "1 + 1 <= 2 / 1",
+                "(#LessThanOrEqualsExpr (#AdditionExpr (#Int64LiteralExpr 1) (#Int64LiteralExpr 1)) (#DivisionExpr (#Int64LiteralExpr 2) (#Int64LiteralExpr 1)))",
+            ),
+            (
+                "1 + 1 >= 2 / 1",
+                "(#GreaterThanOrEqualsExpr (#AdditionExpr (#Int64LiteralExpr 1) (#Int64LiteralExpr 1)) (#DivisionExpr (#Int64LiteralExpr 2) (#Int64LiteralExpr 1)))",
+            ),
+            ("x =~ y", "(#MatchExpr (#IdentifierExpr x) (#IdentifierExpr y))"),
+            ("x !~ y", "(#NotMatchExpr (#IdentifierExpr x) (#IdentifierExpr y))"),
+            ("int?", "(#OptionalExpr (#IdentifierExpr int))"),
+            ("float | int?", "(#UnionExpr (#IdentifierExpr float) (#OptionalExpr (#IdentifierExpr int)))"),
+            ("float & 7.0", "(#IntersectExpr (#IdentifierExpr float) (#Float64LiteralExpr 7))"),
+            (
+                "f(x: 0)",
+                "(#FunctionCallExpr (#IdentifierExpr f) (#FunctionArgumentsExpr (#QualificationExpr (#IdentifierExpr x) (#Int64LiteralExpr 0))))",
+            ),
+            (
+                "(a: f(x: 0))",
+                "(#ParenthesizedExpr (#QualificationExpr (#IdentifierExpr a) (#FunctionCallExpr (#IdentifierExpr f) (#FunctionArgumentsExpr (#QualificationExpr (#IdentifierExpr x) (#Int64LiteralExpr 0))))))",
+            ),
+            ("1..9", "(#RangeExpr (#Int64LiteralExpr 1) (#Int64LiteralExpr 9))"),
+            ("x in 1..9", "(#InExpr (#IdentifierExpr x) (#RangeExpr (#Int64LiteralExpr 1) (#Int64LiteralExpr 9)))"),
+            ("x is Widget", "(#IsExpr (#IdentifierExpr x) (#IdentifierExpr Widget))"),
+            ("x as Float64", "(#AsExpr (#IdentifierExpr x) (#BuiltInTypeExpr Float64))"),
+            (
+                "n: Int64 = x as Int64",
+                "(#IntersectAssignValueExpr (#QualificationExpr (#IdentifierExpr n) (#BuiltInTypeExpr Int64)) (#AsExpr (#IdentifierExpr x) (#BuiltInTypeExpr Int64)))",
+            ),
+            (
+                "1 when n == 0\n| n * f(n - 1) when n > 0",
+                "(#UnionExpr (#WhenExpr (#Int64LiteralExpr 1) (#EqualsExpr (#IdentifierExpr n) (#Int64LiteralExpr 0))) (#WhenExpr (#MultiplicationExpr (#IdentifierExpr n) (#FunctionCallExpr (#IdentifierExpr f) (#FunctionArgumentsExpr (#SubtractionExpr (#IdentifierExpr n) (#Int64LiteralExpr 1))))) (#GreaterThanExpr (#IdentifierExpr n) (#Int64LiteralExpr 0))))",
+            ),
+            (
+                "f: (n: int) -> int = 1 when n == 0\n| n * f(n-1) when n > 0",
+                "(#IntersectAssignValueExpr (#QualificationExpr (#IdentifierExpr f) (#FunctionArrowExpr (#ParenthesizedExpr (#QualificationExpr (#IdentifierExpr n) (#IdentifierExpr int))) (#IdentifierExpr int))) (#UnionExpr (#WhenExpr (#Int64LiteralExpr 1) (#EqualsExpr (#IdentifierExpr n) (#Int64LiteralExpr 0))) (#WhenExpr (#MultiplicationExpr (#IdentifierExpr n) (#FunctionCallExpr (#IdentifierExpr f) (#FunctionArgumentsExpr (#IdentifierExpr n-1)))) (#GreaterThanExpr (#IdentifierExpr n) (#Int64LiteralExpr 0)))))",
+            ),
+            (
+                "x = y + z where {y: 3, z: 5}",
+                "(#IntersectAssignValueExpr (#IdentifierExpr x) (#WhereExpr (#AdditionExpr (#IdentifierExpr y) (#IdentifierExpr z)) (#RecordExpr (#QualificationExpr (#IdentifierExpr y) (#Int64LiteralExpr 3)) (#QualificationExpr (#IdentifierExpr z) (#Int64LiteralExpr 5)))))",
+            ),
         ];
 
-        tests.iter().for_each(|test| check(test));
+        tests.iter().for_each(|(source_code, expected_tree)| check(source_code, expected_tree));
+    }
+
+    #[test]
+    fn test_empty_input() {
+        check_errors("", &[(DiagnosticCode::ExpectedExpression, 0, 0)]);
+        check_errors("  \n", &[(DiagnosticCode::ExpectedExpression, 3, 3)]);
+    }
+
+    #[test]
+    fn test_missing_closing_punctuation() {
+        check_errors("(1 + 2", &[(DiagnosticCode::ExpectedRightParenthesis, 6, 6)]);
+        check_errors("(a, b", &[(DiagnosticCode::ExpectedRightParenthesis, 5, 5)]);
+        check_errors("f(x: 0", &[(DiagnosticCode::ExpectedRightParenthesis, 6, 6)]);
+        check_errors("{a: 1, b: 2", &[(DiagnosticCode::ExpectedRightBrace, 11, 11)]);
+        check_errors("[1, 2", &[(DiagnosticCode::ExpectedRightBracket, 5, 5)]);
+        check_errors("(", &[(DiagnosticCode::ExpectedExpression, 1, 1)]);
+    }
+
+    #[test]
+    fn test_unexpected_tokens() {
+        check_errors("a + * b", &[
+            (DiagnosticCode::ExpectedExpression, 4, 5),
+            (DiagnosticCode::ExpectedEndOfInput, 6, 7),
+        ]);
+        check_errors("{a b}", &[(DiagnosticCode::ExpectedRightBrace, 3, 4)]);
+        check_errors("(x))", &[(DiagnosticCode::ExpectedEndOfInput, 3, 4)]);
+        check_errors("{,}", &[(DiagnosticCode::ExpectedExpression, 1, 2)]);
+    }
+
+    #[test]
+    fn test_scanner_errors() {
+        check_errors("x + ‽", &[(DiagnosticCode::UnrecognizedCharacter, 4, 7)]);
+        check_errors("\"abc\n", &[(DiagnosticCode::UnclosedStringLiteral, 0, 4)]);
+        check_errors("'abc\n", &[(DiagnosticCode::UnclosedCharacterLiteral, 0, 4)]);
+        check_errors(r#"{a: "x\qy\u{110000}", b: 'ok'}"#, &[
+            (DiagnosticCode::InvalidEscapeSequence, 6, 8),
+            (DiagnosticCode::InvalidEscapeSequence, 9, 19),
+        ]);
+    }
+
+    #[test]
+    fn test_invalid_string_literal_error_node() {
+        let source_code = r#"x: "a\qb" + 1"#;
+        let scan_result = scanner::scan(source_code);
+        let expression = parse_expression(&scan_result);
+
+        assert_eq!(
+            "(#QualificationExpr (#IdentifierExpr x) (#AdditionExpr (#ErrorExpr) (#Int64LiteralExpr 1)))",
+            to_compact_s_expression(&expression.model(), source_code)
+        );
+
+        let syntax_tree = &expression.syntax_tree;
+        let error = (0..syntax_tree.len() as u32).map(NodeId).find(|&id| *syntax_tree.kind(id) == NodeKind::Error);
+        assert_eq!(r#""a\qb""#, syntax_tree.source_position(error.unwrap()).get_text(source_code));
+    }
+
+    #[test]
+    fn test_invalid_number_literals() {
+        check_errors("99999999999999999999", &[(DiagnosticCode::IntegerLiteralOutOfRange, 0, 20)]);
+        check_errors("x + 0x1_0000_0000_0000_0000", &[(DiagnosticCode::IntegerLiteralOutOfRange, 4, 27)]);
+        check_errors("-9223372036854775809", &[(DiagnosticCode::IntegerLiteralOutOfRange, 0, 20)]);
+        check_errors("[0x, 1e, 0b12, 2.5E+]", &[
+            (DiagnosticCode::InvalidIntegerLiteral, 1, 3),
+            (DiagnosticCode::InvalidFloatingPointLiteral, 5, 7),
+            (DiagnosticCode::InvalidIntegerLiteral, 9, 13),
+            (DiagnosticCode::InvalidFloatingPointLiteral, 15, 20),
+        ]);
+        check_errors("[1e999, 1_, 1__0, 0x_1]", &[
+            (DiagnosticCode::FloatingPointLiteralOutOfRange, 1, 6),
+            (DiagnosticCode::InvalidIntegerLiteral, 8, 10),
+            (DiagnosticCode::InvalidIntegerLiteral, 12, 16),
+            (DiagnosticCode::InvalidIntegerLiteral, 18, 22),
+        ]);
+    }
+
+    #[test]
+    fn test_number_literal_values() {
+        for (source_code, expected_value) in [
+            ("0xFF", 255), ("0o17", 15), ("0b1010", 10), ("1_000_000", 1000000),
+            ("-9223372036854775808", i64::MIN), ("-0x8000_0000_0000_0000", i64::MIN),
+        ] {
+            let scan_result = scanner::scan(source_code);
+            let expression = parse_expression(&scan_result);
+            assert!(expression.diagnostics.is_empty());
+
+            match *expression.model() {
+                Expr::Int64Literal { value, .. } => assert_eq!(expected_value, value),
+                _ => panic!("Expected an integer literal for '{}'.", source_code),
+            }
+        }
+
+        for (source_code, expected_value) in [
+            ("1.5e-3", 0.0015), ("6E23", 6e23), ("1_000.5", 1000.5),
+        ] {
+            let scan_result = scanner::scan(source_code);
+            let expression = parse_expression(&scan_result);
+            assert!(expression.diagnostics.is_empty());
+
+            match *expression.model() {
+                Expr::Float64Literal { value, .. } => assert_eq!(expected_value, value),
+                _ => panic!("Expected a floating point literal for '{}'.", source_code),
+            }
+        }
+    }
+
+    // Summarizes the shape of a parsed expression, showing sequences item by item and error nodes as "<error>".
+    fn recovered_tree(expr: &Expr, source_code: &str) -> String {
+        let list = |items: &SharedVector<Box<Expr>>| items.iter()
+            .map(|item| recovered_tree(item, source_code))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        return match expr {
+            Expr::ArrayLiteral { elements, .. } => format!("[{}]", list(elements)),
+            Expr::Error { .. } => "<error>".to_string(),
+            Expr::FunctionArguments { items, .. } => format!("({})", list(items)),
+            Expr::FunctionCall { function_reference, argument, .. } =>
+                format!("{}{}", recovered_tree(function_reference, source_code), recovered_tree(argument, source_code)),
+            Expr::IntersectAssignValue { lhs, rhs, .. } =>
+                format!("{} = {}", recovered_tree(lhs, source_code), recovered_tree(rhs, source_code)),
+            Expr::Parenthesized { inner_expr, .. } => format!("({})", recovered_tree(inner_expr, source_code)),
+            Expr::Qualify { lhs, rhs, .. } =>
+                format!("{}: {}", recovered_tree(lhs, source_code), recovered_tree(rhs, source_code)),
+            Expr::Record { items, .. } => format!("{{{}}}", list(items)),
+            Expr::SourceFile { items, .. } => list(items),
+            _ => expr.get_source_position().get_text(source_code).to_string(),
+        };
+    }
+
+    #[test]
+    fn test_error_recovery_corpus() {
+        use DiagnosticCode::*;
+
+        let tests: Vec<(&str, &str, Vec<DiagnosticCode>)> = vec![
+            // Stray operators
+            ("{a: 1, b: * 2, c: 3}", "{a: 1, b: <error>, c: 3}", vec![ExpectedExpression]),
+            ("{a: 1, * , c: 3}", "{a: 1, <error>, c: 3}", vec![ExpectedExpression]),
+            ("[1, 2 3, 4]", "[1, 2, 4]", vec![ExpectedRightBracket]),
+            ("(1, 2 3, 4)", "(1, 2, 4)", vec![ExpectedRightParenthesis]),
+            ("f(x: 1 y, z: 2)", "f(x: 1, z: 2)", vec![ExpectedRightParenthesis]),
+            ("(a b)", "(a)", vec![ExpectedRightParenthesis]),
+            ("(a b, c)", "(a, c)", vec![ExpectedRightParenthesis]),
+
+            // Missing separators
+            ("{a: 1 b: 2, c: 3}", "{a: 1, c: 3}", vec![ExpectedRightBrace]),
+            ("{a: 1; b: 2; c: 3}", "{a: 1, b: 2, c: 3}", vec![ExpectedRightBrace, ExpectedRightBrace]),
+
+            // Missing or mismatched closing punctuation
+            ("{a: [1, 2, b: 3}", "{a: [1, 2, b: 3]}", vec![ExpectedRightBracket]),
+            ("{a: (1 + 2, b: 3}", "{a: (1 + 2, b: 3)}", vec![ExpectedRightParenthesis]),
+            ("{a: {b: 1, c: 2}", "{a: {b: 1, c: 2}}", vec![ExpectedRightBrace]),
+            ("{a: [1, 2), b: 3}", "{a: [1, 2, b: 3]}", vec![ExpectedRightBracket, ExpectedRightBracket]),
+            ("{a: [1, }", "{a: [1, <error>]}", vec![ExpectedExpression]),
+            ("{a: 1 ], b: 2}", "{a: 1, b: 2}", vec![ExpectedRightBrace]),
+
+            // Malformed nested items
+            ("{a: {x: * }, b: 2}", "{a: {x: <error>}, b: 2}", vec![ExpectedExpression]),
+            ("{a: f(1 2 (3, 4)), b: 2}", "{a: f(1), b: 2}", vec![ExpectedRightParenthesis]),
+            ("{a: ‽, b: 2}", "{a: <error>, b: 2}", vec![UnrecognizedCharacter]),
+        ];
+
+        for (source_code, expected_tree, expected_codes) in tests {
+            let scan_result = scanner::scan(source_code);
+            let expression = parse_expression(&scan_result);
+
+            let actual_codes: Vec<DiagnosticCode> = expression.diagnostics.iter().map(|d| d.code).collect();
+            assert_eq!(expected_codes, actual_codes, "Wrong diagnostics for '{}'", source_code);
+            let tree = recovered_tree(&expression.model(), source_code);
+            assert_eq!(expected_tree, tree, "Wrong tree for '{}'", source_code);
+        }
+    }
+
+    fn check_source_file(source_code: &str, expected_tree: &str, expected_codes: Vec<DiagnosticCode>) {
+        let mut scan_result = scanner::scan(source_code);
+
+        scan_result = leading_trailing_documentation::filter(scan_result);
+
+        let source_file = parse_source_file(&scan_result);
+
+        match *source_file.model() {
+            Expr::SourceFile { source_position, .. } => {
+                assert_eq!(0, source_position.start_offset);
+                assert_eq!(source_code.len() as u32, source_position.end_offset);
+            }
+            _ => panic!("Expected a source file expression."),
+        }
+
+        let actual_codes: Vec<DiagnosticCode> = source_file.diagnostics.iter().map(|d| d.code).collect();
+        assert_eq!(expected_codes, actual_codes, "Wrong diagnostics for '{}'", source_code);
+        let tree = recovered_tree(&source_file.model(), source_code);
+        assert_eq!(expected_tree, tree, "Wrong tree for '{}'", source_code);
+    }
+
+    #[test]
+    fn test_source_files() {
+        check_source_file("", "", vec![]);
+        check_source_file("\n\n", "", vec![]);
+        check_source_file("x: Int64", "x: Int64", vec![]);
+        check_source_file("x: Int64,\ny: String,\n", "x: Int64, y: String", vec![]);
+        check_source_file(
+            "name: String = \"Lligne\",\nversion: {major: 1, minor: 2}\n",
+            "name: String = \"Lligne\", version: {major: 1, minor: 2}",
+            vec![],
+        );
+        check_source_file(
+            "// Leading documentation\nx: Int64,\ny: Bool\n",
+            "// Leading documentation\nx: Int64, y: Bool",
+            vec![],
+        );
+    }
+
+    #[test]
+    fn test_source_file_errors() {
+        use DiagnosticCode::*;
+
+        check_source_file("x: 1 y: 2, z: 3", "x: 1, z: 3", vec![ExpectedEndOfInput]);
+        check_source_file("x: 1,\ny: {a: 1,\nz: 3\n", "x: 1, y: {a: 1, z: 3}", vec![ExpectedRightBrace]);
+        check_source_file("x: 1 }, y: 2", "x: 1, y: 2", vec![ExpectedEndOfInput]);
+        check_source_file("x: *,\ny: 2", "x: <error>, y: 2", vec![ExpectedExpression]);
+    }
+
+    fn text_of<'a>(expr: &Expr, source_code: &'a str) -> &'a str {
+        return expr.get_source_position().get_text(source_code);
+    }
+
+    #[test]
+    fn test_index_expressions() {
+        for (source_code, expected_operand, expected_index) in [
+            ("xs[0]", "xs", "0"),
+            ("m[\"key\"]", "m", "\"key\""),
+            ("xs[i + 1]", "xs", "i + 1"),
+            ("a.b[0]", "b", "0"),
+            ("f(x)[2]", "f(x)", "2"),
+            ("xs[0][1]", "xs[0]", "1"),
+        ] {
+            let scan_result = scanner::scan(source_code);
+            let expression = parse_expression(&scan_result);
+            assert!(expression.diagnostics.is_empty());
+
+            let mut model = &expression.model();
+            if let Expr::FieldReference { child, .. } = &**model {
+                model = child;
+            }
+
+            match &**model {
+                Expr::Index { source_position, operand, index } => {
+                    assert_eq!(source_code.len() as u32, source_position.end_offset);
+                    assert_eq!(expected_operand, text_of(operand, source_code));
+                    assert_eq!(expected_index, text_of(index, source_code));
+                }
+                _ => panic!("Expected an index expression for '{}'.", source_code),
+            }
+        }
+    }
+
+    #[test]
+    fn test_slice_expressions() {
+        let source_code = "xs[1..3]";
+        let scan_result = scanner::scan(source_code);
+        let expression = parse_expression(&scan_result);
+        assert!(expression.diagnostics.is_empty());
+
+        match *expression.model() {
+            Expr::Slice { source_position, ref operand, ref first, ref last } => {
+                assert_eq!(SourcePos { start_offset: 0, end_offset: 8 }, source_position);
+                assert_eq!("xs", text_of(operand, source_code));
+                assert_eq!("1", text_of(first, source_code));
+                assert_eq!("3", text_of(last, source_code));
+            }
+            _ => panic!("Expected a slice expression."),
+        }
+
+        check(
+            "xs[a + 1..b - 1]",
+            "(#SliceExpr (#IdentifierExpr xs) (#AdditionExpr (#IdentifierExpr a) (#Int64LiteralExpr 1)) (#SubtractionExpr (#IdentifierExpr b) (#Int64LiteralExpr 1)))",
+        );
+        check(
+            "x in xs[1..3]",
+            "(#InExpr (#IdentifierExpr x) (#SliceExpr (#IdentifierExpr xs) (#Int64LiteralExpr 1) (#Int64LiteralExpr 3)))",
+        );
+    }
+
+    #[test]
+    fn test_index_errors() {
+        check_errors("xs[0", &[(DiagnosticCode::ExpectedRightBracket, 4, 4)]);
+        check_errors("xs[]", &[(DiagnosticCode::ExpectedExpression, 3, 4)]);
+        check_errors("{a: xs[0, b: 1}", &[(DiagnosticCode::ExpectedRightBracket, 8, 9)]);
+    }
+
+    #[test]
+    fn test_as_precedence() {
+        let source_code = "y: -x as Float64 * 2.0 is Float64";
+        let scan_result = scanner::scan(source_code);
+        let expression = parse_expression(&scan_result);
+        assert!(expression.diagnostics.is_empty());
+
+        let model = expression.model();
+        let Expr::Qualify { rhs: ref qualification, .. } = *model else {
+            panic!("Expected a qualification.")
+        };
+        let Expr::Is { lhs: ref product, .. } = **qualification else {
+            panic!("Expected an 'is' expression.")
+        };
+        let Expr::Multiplication { lhs: ref conversion, .. } = **product else {
+            panic!("Expected a multiplication.")
+        };
+        let Expr::As { ref lhs, ref rhs, source_position } = **conversion else {
+            panic!("Expected an 'as' expression.")
+        };
+
+        assert_eq!("-x as Float64", source_position.get_text(source_code));
+        assert_eq!("-x", text_of(lhs, source_code));
+        assert_eq!("Float64", text_of(rhs, source_code));
+    }
+
+    #[test]
+    fn test_long_string_literal() {
+        let source_code = format!("{{ a: `{}\n}}", "long ".repeat(20000));
+        let scan_result = scanner::scan(&source_code);
+        let expression = parse_expression(&scan_result);
+        assert!(expression.diagnostics.is_empty());
+
+        match *expression.model() {
+            Expr::Record { ref items, source_position } => {
+                assert_eq!(source_code.len() as u32, source_position.end_offset);
+                match *items[0] {
+                    Expr::Qualify { ref rhs, .. } => match **rhs {
+                        Expr::StringLiteral { ref value, source_position, .. } => {
+                            assert_eq!(100002, source_position.end_offset - source_position.start_offset);
+                            assert_eq!(100000, value.len());
+                        }
+                        _ => panic!("Expected a string literal."),
+                    },
+                    _ => panic!("Expected a qualification."),
+                }
+            }
+            _ => panic!("Expected a record."),
+        }
+    }
+
+    #[test]
+    fn test_string_literal_values() {
+        for (source_code, expected_delimiters, expected_value) in [
+            (r#""abc""#, StringDelimiters::DoubleQuotes, "abc"),
+            (r#""a\tb\u{1F600}\"""#, StringDelimiters::DoubleQuotes, "a\tb😀\""),
+            (r#"'\x41\n'"#, StringDelimiters::SingleQuotes, "A\n"),
+            ("` line one\n  ` line two\n", StringDelimiters::BackTicksMultiline, " line one\n line two"),
+            ("`raw \\n`", StringDelimiters::BackTicksMultiline, "raw \\n`"),
+        ] {
+            let scan_result = scanner::scan(source_code);
+            let expression = parse_expression(&scan_result);
+            assert!(expression.diagnostics.is_empty());
+
+            match *expression.model() {
+                Expr::StringLiteral { delimiters, ref value, .. } => {
+                    assert_eq!(expected_delimiters, delimiters);
+                    assert_eq!(expected_value, value, "Wrong value for '{}'", source_code);
+                }
+                _ => panic!("Expected a string literal for '{}'.", source_code),
+            }
+        }
+    }
+
+    #[test]
+    fn test_error_nodes() {
+        let scan_result = scanner::scan("(a + )");
+        let expression = parse_expression(&scan_result);
+
+        match *expression.model() {
+            Expr::Parenthesized { ref inner_expr, .. } => match **inner_expr {
+                Expr::Addition { ref rhs, .. } => match **rhs {
+                    Expr::Error { source_position } => assert_eq!(5, source_position.start_offset),
+                    _ => panic!("Expected an error node."),
+                },
+                _ => panic!("Expected an addition."),
+            },
+            _ => panic!("Expected a parenthesized expression."),
+        }
+        assert_eq!(1, expression.diagnostics.len());
+    }
+
+    #[test]
+    fn test_diagnostics_in_files() {
+        let mut source_database = new_source_database();
+        let file_id_a = source_database.add_file("a.llg", "x = 1\n".to_string());
+        let file_id_b = source_database.add_file("b.llg", "y = 2,\nz = (3\n".to_string());
+
+        let file_b = source_database.file(file_id_b).unwrap();
+        let scan_result = scanner::scan_file(file_id_b, &file_b.source_code);
+        let source_file = parse_source_file(&scan_result);
+
+        assert_eq!(file_id_b, source_file.file_id);
+        assert_eq!(1, source_file.diagnostics.len());
+
+        let diagnostic = &source_file.diagnostics[0];
+        assert_eq!(file_id_b, diagnostic.file_id);
+        assert_ne!(file_id_a, diagnostic.file_id);
+        assert_eq!("b.llg:3:1", source_database.origin(diagnostic.file_span()).unwrap().to_string());
+    }
+
+    #[test]
+    fn test_out_of_range_literal_node() {
+        let source_code = "[1, 18446744073709551616]";
+        let scan_result = scanner::scan(source_code);
+        let expression = parse_expression(&scan_result);
+
+        match *expression.model() {
+            Expr::ArrayLiteral { ref elements, .. } => match *elements[1] {
+                Expr::Error { source_position } =>
+                    assert_eq!("18446744073709551616", source_position.get_text(source_code)),
+                _ => panic!("Expected an error node."),
+            },
+            _ => panic!("Expected an array literal."),
+        }
+        assert_eq!(DiagnosticCode::IntegerLiteralOutOfRange, expression.diagnostics[0].code);
     }
 }
 
@@ -0,0 +1,140 @@
+//
+// # S-expression printing of Lligne expression trees.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+
+use crate::lligne::code::parsing::expressions::{Expr, StringDelimiters};
+
+//=====================================================================================================================
+
+// Prints an expression tree in the layout of the TypeScript toSExpression: one line per node giving its source range,
+// its tag indented two spaces per level of nesting, and the value, name, or text of a leaf, e.g.
+//
+//   0..5:       #AdditionExpr
+//   0..1:         #IdentifierExpr x
+//   4..5:         #Int64LiteralExpr 1
+//
+pub fn to_s_expression(expr: &Expr, source_code: &str) -> String {
+    let mut result = String::new();
+    write_s_expression(expr, source_code, "", &mut result);
+    return result;
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Appends the lines for one node and (recursively) its children.
+fn write_s_expression(expr: &Expr, source_code: &str, indent: &str, result: &mut String) {
+    let source_position = expr.get_source_position();
+    let range = format!("{}..{}:", source_position.start_offset, source_position.end_offset);
+
+    *result += &format!("{:<12}{}{}", range, indent, expr.tag());
+    if let Some(leaf) = leaf_text(expr, source_code) {
+        *result += " ";
+        *result += &leaf;
+    }
+    *result += "\n";
+
+    let child_indent = format!("{}  ", indent);
+    for child in expr.children() {
+        write_s_expression(child, source_code, &child_indent, result);
+    }
+}
+
+//=====================================================================================================================
+
+// Prints an expression tree as a compact, single-line, parenthesized S-expression without source positions, e.g.
+//
+//   (#AdditionExpr (#IdentifierExpr x) (#Int64LiteralExpr 1))
+//
+pub fn to_compact_s_expression(expr: &Expr, source_code: &str) -> String {
+    let mut result = format!("({}", expr.tag());
+
+    if let Some(leaf) = leaf_text(expr, source_code) {
+        result += " ";
+        result += &leaf;
+    }
+
+    for child in expr.children() {
+        result += " ";
+        result += &to_compact_s_expression(child, source_code);
+    }
+
+    return result + ")";
+}
+
+//=====================================================================================================================
+
+// Describes the value, name, or text of a leaf expression. Quoted strings keep their quotes; string blocks and
+// documentation are given as their decoded value or source text. Line feeds are written as "\n" to keep each node
+// on one line.
+fn leaf_text(expr: &Expr, source_code: &str) -> Option<String> {
+    let text = expr.get_source_position().get_text(source_code);
+
+    return match expr {
+        Expr::BooleanLiteral { value, .. } => Some(value.to_string()),
+        Expr::BuiltInType { .. } => Some(text.to_string()),
+        Expr::Float64Literal { value, .. } => Some(value.to_string()),
+        Expr::Identifier { .. } => Some(text.to_string()),
+        Expr::Int64Literal { value, .. } => Some(value.to_string()),
+        Expr::LeadingDocumentation { .. } => Some(text.to_string()),
+        Expr::StringLiteral { delimiters, value, .. } => Some(match delimiters {
+            StringDelimiters::SingleQuotes => format!("'{}'", value),
+            StringDelimiters::DoubleQuotes => format!("\"{}\"", value),
+            StringDelimiters::BackTicks => format!("`{}`", value),
+            StringDelimiters::SingleQuotesMultiline |
+            StringDelimiters::DoubleQuotesMultiline |
+            StringDelimiters::BackTicksMultiline => value.clone(),
+        }),
+        Expr::TrailingDocumentation { .. } => Some(text.to_string()),
+        _ => None,
+    }.map(|leaf| leaf.replace('\n', "\\n"));
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::lligne::code::parsing::parser;
+    use crate::lligne::code::scanning::scanner;
+    use super::*;
+
+    #[test]
+    fn test_s_expression() {
+        let source_code = "f(x: 'a') + -2.5";
+        let scan_result = scanner::scan(source_code);
+        let expression = parser::parse_expression(&scan_result);
+
+        assert_eq!(
+            "0..16:      #AdditionExpr\n\
+             0..9:         #FunctionCallExpr\n\
+             0..1:           #IdentifierExpr f\n\
+             1..9:           #FunctionArgumentsExpr\n\
+             2..8:             #QualificationExpr\n\
+             2..3:               #IdentifierExpr x\n\
+             5..8:               #SingleQuotedStringExpr 'a'\n\
+             12..16:       #NegationExpr\n\
+             13..16:         #Float64LiteralExpr 2.5\n",
+            to_s_expression(&expression.model(), source_code)
+        );
+    }
+
+    #[test]
+    fn test_compact_s_expression() {
+        let source_code = "{b: true, s: \"t\"} & [1, 'r']";
+        let scan_result = scanner::scan(source_code);
+        let expression = parser::parse_expression(&scan_result);
+
+        assert_eq!(
+            "(#IntersectExpr \
+               (#RecordExpr \
+                 (#QualificationExpr (#IdentifierExpr b) (#BooleanLiteralExpr true)) \
+                 (#QualificationExpr (#IdentifierExpr s) (#DoubleQuotedStringExpr \"t\"))) \
+               (#ArrayLiteralExpr (#Int64LiteralExpr 1) (#SingleQuotedStringExpr 'r')))",
+            to_compact_s_expression(&expression.model(), source_code)
+        );
+    }
+}
+
+//=====================================================================================================================
@@ -0,0 +1,413 @@
+//
+// # Arena-allocated syntax trees of Lligne expressions: node identities, parent links, and child indices.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+// The parser adds each node to the arena as soon as the node is complete, i.e. after its children, so nodes are
+// numbered in post-order and the root is the last node. The children of a node occupy a range of one shared list of
+// node ids rather than a vector of their own. The formatter prints the arena directly; the boxed Expr tree remains
+// available through to_expr for the code that folds, visits, or serializes expressions.
+//
+
+use std::ops::Range;
+use shared_vector::SharedVector;
+use crate::lligne::code::parsing::expressions::{Expr, StringDelimiters};
+use crate::lligne::code::util::source_pos::SourcePos;
+
+//=====================================================================================================================
+
+// Identifies one node of a syntax tree: its index in the tree's table of nodes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub u32);
+
+//=====================================================================================================================
+
+// The kind of a node together with the value of a literal: the counterpart of an Expr variant without its children.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeKind {
+    Addition,
+    ArrayLiteral,
+    As,
+    BooleanLiteral(bool),
+    BuiltInType,
+    Division,
+    Document,
+    Equals,
+    Error,
+    FieldReference,
+    Float64Literal(f64),
+    FunctionArguments,
+    FunctionArrow,
+    FunctionCall,
+    GreaterThan,
+    GreaterThanOrEquals,
+    Identifier,
+    In,
+    Index,
+    Int64Literal(i64),
+    Intersect,
+    IntersectAssignValue,
+    IntersectDefaultValue,
+    IntersectLowPrecedence,
+    Is,
+    LeadingDocumentation,
+    LessThan,
+    LessThanOrEquals,
+    LogicalAnd,
+    LogicalNotOperation,
+    LogicalOr,
+    Match,
+    Multiplication,
+    NegationOperation,
+    NotEquals,
+    NotMatch,
+    Optional,
+    Parenthesized,
+    Qualify,
+    Range,
+    Record,
+    Slice,
+    SourceFile,
+    StringLiteral(StringDelimiters, String),
+    Subtraction,
+    TrailingDocumentation,
+    Union,
+    Unit,
+    When,
+    Where,
+}
+
+//=====================================================================================================================
+
+// One entry of the table of nodes: the kind and source position of the node plus its links within the tree.
+struct Node {
+    kind: NodeKind,
+    source_position: SourcePos,
+    parent: Option<NodeId>,
+    child_index: u32,
+    children: Range<u32>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// A table of all the nodes of an expression tree, so that a node can be found from a source position and any node
+// can be walked upward to the root (the counterpart of the parent-child CompositeTree of the TypeScript parser).
+pub struct SyntaxTree {
+    nodes: Vec<Node>,
+    child_ids: Vec<NodeId>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Creates an empty syntax tree, ready for the parser to add nodes to.
+pub fn new_syntax_tree() -> SyntaxTree {
+    return SyntaxTree {
+        nodes: vec![],
+        child_ids: vec![],
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl SyntaxTree {
+    // Adds a node whose children (in source order) have already been added and have no parent yet.
+    pub fn add_node(&mut self, kind: NodeKind, source_position: SourcePos, children: &[NodeId]) -> NodeId {
+        let result = NodeId(self.nodes.len() as u32);
+
+        let first_child = self.child_ids.len() as u32;
+        for (child_index, &child) in children.iter().enumerate() {
+            let child_node = &mut self.nodes[child.0 as usize];
+            child_node.parent = Some(result);
+            child_node.child_index = child_index as u32;
+            self.child_ids.push(child);
+        }
+
+        self.nodes.push(Node {
+            kind,
+            source_position,
+            parent: None,
+            child_index: 0,
+            children: first_child..self.child_ids.len() as u32,
+        });
+
+        return result;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Removes the most recently added node (which must be the given one), e.g. a range that turns out to be the bounds
+    // of a slice, and returns its children, which are then free to be added to another node.
+    pub fn remove_last_node(&mut self, node_id: NodeId) -> Vec<NodeId> {
+        assert_eq!(node_id.0 as usize, self.nodes.len() - 1, "Only the last node can be removed.");
+
+        let node = self.nodes.pop().unwrap();
+        let result: Vec<NodeId> = self.child_ids.drain(node.children.start as usize..).collect();
+
+        for &child in &result {
+            self.nodes[child.0 as usize].parent = None;
+            self.nodes[child.0 as usize].child_index = 0;
+        }
+
+        return result;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the number of nodes in the tree.
+    pub fn len(&self) -> usize {
+        return self.nodes.len();
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Whether the tree has no nodes (never true once parsed: every tree has a root).
+    pub fn is_empty(&self) -> bool {
+        return self.nodes.is_empty();
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the root of the tree: the last node added.
+    pub fn root(&self) -> NodeId {
+        return NodeId(self.nodes.len() as u32 - 1);
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the kind of a node.
+    pub fn kind(&self, node_id: NodeId) -> &NodeKind {
+        return &self.nodes[node_id.0 as usize].kind;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the source position of a node.
+    pub fn source_position(&self, node_id: NodeId) -> SourcePos {
+        return self.nodes[node_id.0 as usize].source_position;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the parent of a node, or None for the root.
+    pub fn parent(&self, node_id: NodeId) -> Option<NodeId> {
+        return self.nodes[node_id.0 as usize].parent;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the position of a node among the children of its parent (zero for the root).
+    pub fn child_index(&self, node_id: NodeId) -> u32 {
+        return self.nodes[node_id.0 as usize].child_index;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the children of a node in source order.
+    pub fn children(&self, node_id: NodeId) -> impl Iterator<Item=NodeId> + '_ {
+        let children = self.nodes[node_id.0 as usize].children.clone();
+        return self.child_ids[children.start as usize..children.end as usize].iter().copied();
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the ancestors of a node from its parent up to the root.
+    pub fn ancestors(&self, node_id: NodeId) -> impl Iterator<Item=NodeId> + '_ {
+        return std::iter::successors(self.parent(node_id), |&ancestor| self.parent(ancestor));
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Finds the innermost node whose source range contains the given offset, e.g. the node under the cursor in the
+    // editor. A node contains the offsets from its start through its end so that a cursor just after a token still
+    // finds it; where two children touch, the earlier wins. Returns None if the offset lies outside the root.
+    pub fn node_at_offset(&self, offset: u32) -> Option<NodeId> {
+        let contains = |node_id: NodeId| {
+            let source_position = self.source_position(node_id);
+            return source_position.start_offset <= offset && offset <= source_position.end_offset;
+        };
+
+        if !contains(self.root()) {
+            return None;
+        }
+
+        let mut result = self.root();
+        while let Some(child) = self.children(result).find(|&child| contains(child)) {
+            result = child;
+        }
+
+        return Some(result);
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Builds the boxed expression tree of a node and its descendants.
+    pub fn to_expr(&self, node_id: NodeId) -> Box<Expr> {
+        let source_position = self.source_position(node_id);
+        let mut children = self.children(node_id).map(|child| self.to_expr(child));
+        let mut child = || children.next().unwrap();
+
+        let expr = match self.kind(node_id) {
+            NodeKind::Addition => Expr::Addition { source_position, lhs: child(), rhs: child() },
+            NodeKind::ArrayLiteral => Expr::ArrayLiteral { source_position, elements: to_shared(children) },
+            NodeKind::As => Expr::As { source_position, lhs: child(), rhs: child() },
+            NodeKind::BooleanLiteral(value) => Expr::BooleanLiteral { source_position, value: *value },
+            NodeKind::BuiltInType => Expr::BuiltInType { source_position },
+            NodeKind::Division => Expr::Division { source_position, lhs: child(), rhs: child() },
+            NodeKind::Document => Expr::Document { source_position, lhs: child(), rhs: child() },
+            NodeKind::Equals => Expr::Equals { source_position, lhs: child(), rhs: child() },
+            NodeKind::Error => Expr::Error { source_position },
+            NodeKind::FieldReference => Expr::FieldReference { source_position, parent: child(), child: child() },
+            NodeKind::Float64Literal(value) => Expr::Float64Literal { source_position, value: *value },
+            NodeKind::FunctionArguments => Expr::FunctionArguments { source_position, items: to_shared(children) },
+            NodeKind::FunctionArrow => Expr::FunctionArrow { source_position, argument: child(), result: child() },
+            NodeKind::FunctionCall =>
+                Expr::FunctionCall { source_position, function_reference: child(), argument: child() },
+            NodeKind::GreaterThan => Expr::GreaterThan { source_position, lhs: child(), rhs: child() },
+            NodeKind::GreaterThanOrEquals => Expr::GreaterThanOrEquals { source_position, lhs: child(), rhs: child() },
+            NodeKind::Identifier => Expr::Identifier { source_position },
+            NodeKind::In => Expr::In { source_position, lhs: child(), rhs: child() },
+            NodeKind::Index => Expr::Index { source_position, operand: child(), index: child() },
+            NodeKind::Int64Literal(value) => Expr::Int64Literal { source_position, value: *value },
+            NodeKind::Intersect => Expr::Intersect { source_position, lhs: child(), rhs: child() },
+            NodeKind::IntersectAssignValue =>
+                Expr::IntersectAssignValue { source_position, lhs: child(), rhs: child() },
+            NodeKind::IntersectDefaultValue =>
+                Expr::IntersectDefaultValue { source_position, lhs: child(), rhs: child() },
+            NodeKind::IntersectLowPrecedence =>
+                Expr::IntersectLowPrecedence { source_position, lhs: child(), rhs: child() },
+            NodeKind::Is => Expr::Is { source_position, lhs: child(), rhs: child() },
+            NodeKind::LeadingDocumentation => Expr::LeadingDocumentation { source_position },
+            NodeKind::LessThan => Expr::LessThan { source_position, lhs: child(), rhs: child() },
+            NodeKind::LessThanOrEquals => Expr::LessThanOrEquals { source_position, lhs: child(), rhs: child() },
+            NodeKind::LogicalAnd => Expr::LogicalAnd { source_position, lhs: child(), rhs: child() },
+            NodeKind::LogicalNotOperation => Expr::LogicalNotOperation { source_position, operand: child() },
+            NodeKind::LogicalOr => Expr::LogicalOr { source_position, lhs: child(), rhs: child() },
+            NodeKind::Match => Expr::Match { source_position, lhs: child(), rhs: child() },
+            NodeKind::Multiplication => Expr::Multiplication { source_position, lhs: child(), rhs: child() },
+            NodeKind::NegationOperation => Expr::NegationOperation { source_position, operand: child() },
+            NodeKind::NotEquals => Expr::NotEquals { source_position, lhs: child(), rhs: child() },
+            NodeKind::NotMatch => Expr::NotMatch { source_position, lhs: child(), rhs: child() },
+            NodeKind::Optional => Expr::Optional { source_position, operand: child() },
+            NodeKind::Parenthesized => Expr::Parenthesized { source_position, inner_expr: child() },
+            NodeKind::Qualify => Expr::Qualify { source_position, lhs: child(), rhs: child() },
+            NodeKind::Range => Expr::Range { source_position, first: child(), last: child() },
+            NodeKind::Record => Expr::Record { source_position, items: to_shared(children) },
+            NodeKind::Slice => Expr::Slice { source_position, operand: child(), first: child(), last: child() },
+            NodeKind::SourceFile => Expr::SourceFile { source_position, items: to_shared(children) },
+            NodeKind::StringLiteral(delimiters, value) =>
+                Expr::StringLiteral { source_position, delimiters: *delimiters, value: value.clone() },
+            NodeKind::Subtraction => Expr::Subtraction { source_position, lhs: child(), rhs: child() },
+            NodeKind::TrailingDocumentation => Expr::TrailingDocumentation { source_position },
+            NodeKind::Union => Expr::Union { source_position, lhs: child(), rhs: child() },
+            NodeKind::Unit => Expr::Unit { source_position },
+            NodeKind::When => Expr::When { source_position, lhs: child(), rhs: child() },
+            NodeKind::Where => Expr::Where { source_position, lhs: child(), rhs: child() },
+        };
+
+        return Box::new(expr);
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Collects the items of a list expression.
+fn to_shared(items: impl Iterator<Item=Box<Expr>>) -> SharedVector<Box<Expr>> {
+    let mut result = SharedVector::new();
+    for item in items {
+        result.push(item);
+    }
+    return result;
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::lligne::code::parsing::parser;
+    use crate::lligne::code::scanning::scanner;
+    use crate::lligne::code::parsing::visitor::preorder;
+    use super::*;
+
+    #[test]
+    fn test_links() {
+        let source_code = "{a: f(x, y), b: [1, 2 + 3], c: xs[1..2]}";
+        let scan_result = scanner::scan(source_code);
+        let expression = parser::parse_expression(&scan_result);
+
+        let syntax_tree = &expression.syntax_tree;
+
+        assert_eq!(preorder(&expression.model()).count(), syntax_tree.len());
+        assert_eq!(None, syntax_tree.parent(syntax_tree.root()));
+
+        for index in 0..syntax_tree.len() {
+            let node_id = NodeId(index as u32);
+
+            for (child_index, child) in syntax_tree.children(node_id).enumerate() {
+                assert!(child < node_id);
+                assert_eq!(Some(node_id), syntax_tree.parent(child));
+                assert_eq!(child_index as u32, syntax_tree.child_index(child));
+            }
+
+            if node_id != syntax_tree.root() {
+                assert!(syntax_tree.parent(node_id).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn test_deeply_nested_to_expr() {
+        let source_code = format!("{}1{}", "[".repeat(40), "]".repeat(40));
+        let scan_result = scanner::scan(&source_code);
+        let expression = parser::parse_expression(&scan_result);
+
+        assert!(expression.diagnostics.is_empty());
+        assert_eq!(41, expression.syntax_tree.len());
+        assert_eq!(41, preorder(&expression.model()).count());
+    }
+
+    #[test]
+    fn test_node_at_offset() {
+        let source_code = "{a: f(x, y), b: [1, 2 + 3]}";
+        let scan_result = scanner::scan(source_code);
+        let expression = parser::parse_expression(&scan_result);
+
+        let syntax_tree = &expression.syntax_tree;
+
+        let text_at = |offset: u32| {
+            let node_id = syntax_tree.node_at_offset(offset).unwrap();
+            return syntax_tree.source_position(node_id).get_text(source_code);
+        };
+
+        assert_eq!("x", text_at(6));
+        assert_eq!("x", text_at(7));
+        assert_eq!("f", text_at(5));
+        assert_eq!("(x, y)", text_at(8));
+        assert_eq!("3", text_at(24));
+        assert_eq!("2 + 3", text_at(22));
+        assert_eq!(None, syntax_tree.node_at_offset(99));
+
+        let node_id = syntax_tree.node_at_offset(24).unwrap();
+        let kinds: Vec<&NodeKind> = syntax_tree.ancestors(node_id).map(|id| syntax_tree.kind(id)).collect();
+        assert_eq!(vec![&NodeKind::Addition, &NodeKind::ArrayLiteral, &NodeKind::Qualify, &NodeKind::Record], kinds);
+    }
+
+    #[test]
+    fn test_node_at_offset_in_logical_not() {
+        for (source_code, offset) in [("not b", 4), ("x: not b", 7)] {
+            let scan_result = scanner::scan(source_code);
+            let expression = parser::parse_expression(&scan_result);
+            let syntax_tree = &expression.syntax_tree;
+
+            let node_id = syntax_tree.node_at_offset(offset).unwrap();
+            assert_eq!(&NodeKind::Identifier, syntax_tree.kind(node_id));
+            assert_eq!("b", syntax_tree.source_position(node_id).get_text(source_code));
+
+            let parent = syntax_tree.parent(node_id).unwrap();
+            assert_eq!(&NodeKind::LogicalNotOperation, syntax_tree.kind(parent));
+            assert_eq!("not b", syntax_tree.source_position(parent).get_text(source_code));
+        }
+    }
+}
+
+//=====================================================================================================================
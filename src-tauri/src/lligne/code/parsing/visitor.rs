@@ -0,0 +1,411 @@
+//
+// # Read-only traversal of Lligne expression trees.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+
+use crate::lligne::code::parsing::expressions::{Expr, StringDelimiters};
+use crate::lligne::code::util::source_pos::SourcePos;
+
+//=====================================================================================================================
+
+// Visits the nodes of an expression tree by reference. Each kind of expression has its own method receiving the
+// fields of the node; by default it visits the children in source order, so an implementation overrides only the
+// methods for the kinds of expression it cares about (calling walk_expr or visiting children itself to continue
+// below them). Overriding visit_expr sees every node; it should call walk_expr to continue the traversal.
+#[allow(unused_variables)]
+pub trait Visitor {
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_exprs(&mut self, exprs: &[Box<Expr>]) {
+        for expr in exprs {
+            self.visit_expr(expr);
+        }
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    fn visit_addition(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_array_literal(&mut self, source_position: SourcePos, elements: &[Box<Expr>]) {
+        self.visit_exprs(elements);
+    }
+
+    fn visit_as(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_boolean_literal(&mut self, source_position: SourcePos, value: bool) {}
+
+    fn visit_built_in_type(&mut self, source_position: SourcePos) {}
+
+    fn visit_division(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_document(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_equals(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_error(&mut self, source_position: SourcePos) {}
+
+    fn visit_field_reference(&mut self, source_position: SourcePos, parent: &Expr, child: &Expr) {
+        self.visit_expr(parent);
+        self.visit_expr(child);
+    }
+
+    fn visit_float64_literal(&mut self, source_position: SourcePos, value: f64) {}
+
+    fn visit_function_arguments(&mut self, source_position: SourcePos, items: &[Box<Expr>]) {
+        self.visit_exprs(items);
+    }
+
+    fn visit_function_arrow(&mut self, source_position: SourcePos, argument: &Expr, result: &Expr) {
+        self.visit_expr(argument);
+        self.visit_expr(result);
+    }
+
+    fn visit_function_call(&mut self, source_position: SourcePos, function_reference: &Expr, argument: &Expr) {
+        self.visit_expr(function_reference);
+        self.visit_expr(argument);
+    }
+
+    fn visit_greater_than(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_greater_than_or_equals(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_identifier(&mut self, source_position: SourcePos) {}
+
+    fn visit_in(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_index(&mut self, source_position: SourcePos, operand: &Expr, index: &Expr) {
+        self.visit_expr(operand);
+        self.visit_expr(index);
+    }
+
+    fn visit_int64_literal(&mut self, source_position: SourcePos, value: i64) {}
+
+    fn visit_intersect(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_intersect_assign_value(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_intersect_default_value(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_intersect_low_precedence(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_is(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_leading_documentation(&mut self, source_position: SourcePos) {}
+
+    fn visit_less_than(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_less_than_or_equals(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_logical_and(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_logical_not_operation(&mut self, source_position: SourcePos, operand: &Expr) {
+        self.visit_expr(operand);
+    }
+
+    fn visit_logical_or(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_match(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_multiplication(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_negation_operation(&mut self, source_position: SourcePos, operand: &Expr) {
+        self.visit_expr(operand);
+    }
+
+    fn visit_not_equals(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_not_match(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_optional(&mut self, source_position: SourcePos, operand: &Expr) {
+        self.visit_expr(operand);
+    }
+
+    fn visit_parenthesized(&mut self, source_position: SourcePos, inner_expr: &Expr) {
+        self.visit_expr(inner_expr);
+    }
+
+    fn visit_qualify(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_range(&mut self, source_position: SourcePos, first: &Expr, last: &Expr) {
+        self.visit_expr(first);
+        self.visit_expr(last);
+    }
+
+    fn visit_record(&mut self, source_position: SourcePos, items: &[Box<Expr>]) {
+        self.visit_exprs(items);
+    }
+
+    fn visit_slice(&mut self, source_position: SourcePos, operand: &Expr, first: &Expr, last: &Expr) {
+        self.visit_expr(operand);
+        self.visit_expr(first);
+        self.visit_expr(last);
+    }
+
+    fn visit_source_file(&mut self, source_position: SourcePos, items: &[Box<Expr>]) {
+        self.visit_exprs(items);
+    }
+
+    fn visit_string_literal(&mut self, source_position: SourcePos, delimiters: StringDelimiters, value: &str) {}
+
+    fn visit_subtraction(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_trailing_documentation(&mut self, source_position: SourcePos) {}
+
+    fn visit_union(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_unit(&mut self, source_position: SourcePos) {}
+
+    fn visit_when(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+
+    fn visit_where(&mut self, source_position: SourcePos, lhs: &Expr, rhs: &Expr) {
+        self.visit_expr(lhs);
+        self.visit_expr(rhs);
+    }
+}
+
+//=====================================================================================================================
+
+// Dispatches a node to the visitor method for its kind of expression.
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Addition { source_position, lhs, rhs } => visitor.visit_addition(*source_position, lhs, rhs),
+        Expr::ArrayLiteral { source_position, elements } => visitor.visit_array_literal(*source_position, elements),
+        Expr::As { source_position, lhs, rhs } => visitor.visit_as(*source_position, lhs, rhs),
+        Expr::BooleanLiteral { source_position, value } => visitor.visit_boolean_literal(*source_position, *value),
+        Expr::BuiltInType { source_position } => visitor.visit_built_in_type(*source_position),
+        Expr::Division { source_position, lhs, rhs } => visitor.visit_division(*source_position, lhs, rhs),
+        Expr::Document { source_position, lhs, rhs } => visitor.visit_document(*source_position, lhs, rhs),
+        Expr::Equals { source_position, lhs, rhs } => visitor.visit_equals(*source_position, lhs, rhs),
+        Expr::Error { source_position } => visitor.visit_error(*source_position),
+        Expr::FieldReference { source_position, parent, child } =>
+            visitor.visit_field_reference(*source_position, parent, child),
+        Expr::Float64Literal { source_position, value } => visitor.visit_float64_literal(*source_position, *value),
+        Expr::FunctionArguments { source_position, items } => visitor.visit_function_arguments(*source_position, items),
+        Expr::FunctionArrow { source_position, argument, result } =>
+            visitor.visit_function_arrow(*source_position, argument, result),
+        Expr::FunctionCall { source_position, function_reference, argument } =>
+            visitor.visit_function_call(*source_position, function_reference, argument),
+        Expr::GreaterThan { source_position, lhs, rhs } => visitor.visit_greater_than(*source_position, lhs, rhs),
+        Expr::GreaterThanOrEquals { source_position, lhs, rhs } =>
+            visitor.visit_greater_than_or_equals(*source_position, lhs, rhs),
+        Expr::Identifier { source_position } => visitor.visit_identifier(*source_position),
+        Expr::In { source_position, lhs, rhs } => visitor.visit_in(*source_position, lhs, rhs),
+        Expr::Index { source_position, operand, index } => visitor.visit_index(*source_position, operand, index),
+        Expr::Int64Literal { source_position, value } => visitor.visit_int64_literal(*source_position, *value),
+        Expr::Intersect { source_position, lhs, rhs } => visitor.visit_intersect(*source_position, lhs, rhs),
+        Expr::IntersectAssignValue { source_position, lhs, rhs } =>
+            visitor.visit_intersect_assign_value(*source_position, lhs, rhs),
+        Expr::IntersectDefaultValue { source_position, lhs, rhs } =>
+            visitor.visit_intersect_default_value(*source_position, lhs, rhs),
+        Expr::IntersectLowPrecedence { source_position, lhs, rhs } =>
+            visitor.visit_intersect_low_precedence(*source_position, lhs, rhs),
+        Expr::Is { source_position, lhs, rhs } => visitor.visit_is(*source_position, lhs, rhs),
+        Expr::LeadingDocumentation { source_position } => visitor.visit_leading_documentation(*source_position),
+        Expr::LessThan { source_position, lhs, rhs } => visitor.visit_less_than(*source_position, lhs, rhs),
+        Expr::LessThanOrEquals { source_position, lhs, rhs } =>
+            visitor.visit_less_than_or_equals(*source_position, lhs, rhs),
+        Expr::LogicalAnd { source_position, lhs, rhs } => visitor.visit_logical_and(*source_position, lhs, rhs),
+        Expr::LogicalNotOperation { source_position, operand } =>
+            visitor.visit_logical_not_operation(*source_position, operand),
+        Expr::LogicalOr { source_position, lhs, rhs } => visitor.visit_logical_or(*source_position, lhs, rhs),
+        Expr::Match { source_position, lhs, rhs } => visitor.visit_match(*source_position, lhs, rhs),
+        Expr::Multiplication { source_position, lhs, rhs } => visitor.visit_multiplication(*source_position, lhs, rhs),
+        Expr::NegationOperation { source_position, operand } =>
+            visitor.visit_negation_operation(*source_position, operand),
+        Expr::NotEquals { source_position, lhs, rhs } => visitor.visit_not_equals(*source_position, lhs, rhs),
+        Expr::NotMatch { source_position, lhs, rhs } => visitor.visit_not_match(*source_position, lhs, rhs),
+        Expr::Optional { source_position, operand } => visitor.visit_optional(*source_position, operand),
+        Expr::Parenthesized { source_position, inner_expr } =>
+            visitor.visit_parenthesized(*source_position, inner_expr),
+        Expr::Qualify { source_position, lhs, rhs } => visitor.visit_qualify(*source_position, lhs, rhs),
+        Expr::Range { source_position, first, last } => visitor.visit_range(*source_position, first, last),
+        Expr::Record { source_position, items } => visitor.visit_record(*source_position, items),
+        Expr::Slice { source_position, operand, first, last } =>
+            visitor.visit_slice(*source_position, operand, first, last),
+        Expr::SourceFile { source_position, items } => visitor.visit_source_file(*source_position, items),
+        Expr::StringLiteral { source_position, delimiters, value } =>
+            visitor.visit_string_literal(*source_position, *delimiters, value),
+        Expr::Subtraction { source_position, lhs, rhs } => visitor.visit_subtraction(*source_position, lhs, rhs),
+        Expr::TrailingDocumentation { source_position } => visitor.visit_trailing_documentation(*source_position),
+        Expr::Union { source_position, lhs, rhs } => visitor.visit_union(*source_position, lhs, rhs),
+        Expr::Unit { source_position } => visitor.visit_unit(*source_position),
+        Expr::When { source_position, lhs, rhs } => visitor.visit_when(*source_position, lhs, rhs),
+        Expr::Where { source_position, lhs, rhs } => visitor.visit_where(*source_position, lhs, rhs),
+    }
+}
+
+//=====================================================================================================================
+
+// Iterates over an expression tree in preorder: each node before its children, children in source order.
+pub struct Preorder<'a> {
+    pending: Vec<&'a Expr>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Starts a preorder iteration of the given expression and its descendants.
+pub fn preorder(expr: &Expr) -> Preorder<'_> {
+    return Preorder {
+        pending: vec![expr],
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl<'a> Iterator for Preorder<'a> {
+    type Item = &'a Expr;
+
+    fn next(&mut self) -> Option<&'a Expr> {
+        let expr = self.pending.pop()?;
+        self.pending.extend(expr.children().into_iter().rev());
+        return Some(expr);
+    }
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::lligne::code::parsing::parser;
+    use crate::lligne::code::scanning::scanner;
+    use super::*;
+
+    // Collects the names of identifiers, skipping the right hand sides of qualifications.
+    struct IdentifierCollector<'a> {
+        source_code: &'a str,
+        names: Vec<&'a str>,
+    }
+
+    impl<'a> Visitor for IdentifierCollector<'a> {
+        fn visit_identifier(&mut self, source_position: SourcePos) {
+            self.names.push(source_position.get_text(self.source_code));
+        }
+
+        fn visit_qualify(&mut self, _source_position: SourcePos, lhs: &Expr, _rhs: &Expr) {
+            self.visit_expr(lhs);
+        }
+    }
+
+    // Counts every node through visit_expr.
+    struct NodeCounter {
+        count: usize,
+    }
+
+    impl Visitor for NodeCounter {
+        fn visit_expr(&mut self, expr: &Expr) {
+            self.count += 1;
+            walk_expr(self, expr);
+        }
+    }
+
+    #[test]
+    fn test_visitor() {
+        let source_code = "{a: T, b = f(c, [d, e.g]), h: xs[i..j]}";
+        let scan_result = scanner::scan(source_code);
+        let expression = parser::parse_expression(&scan_result);
+
+        let mut collector = IdentifierCollector { source_code, names: vec![] };
+        collector.visit_expr(&expression.model());
+        assert_eq!(vec!["a", "b", "f", "c", "d", "e", "g", "h"], collector.names);
+
+        let mut counter = NodeCounter { count: 0 };
+        counter.visit_expr(&expression.model());
+        assert_eq!(preorder(&expression.model()).count(), counter.count);
+    }
+
+    #[test]
+    fn test_preorder() {
+        let source_code = "a + b * -c";
+        let scan_result = scanner::scan(source_code);
+        let expression = parser::parse_expression(&scan_result);
+
+        let tags: Vec<&str> = preorder(&expression.model()).map(|expr| expr.tag()).collect();
+
+        assert_eq!(
+            vec!["#AdditionExpr", "#IdentifierExpr", "#MultiplicationExpr", "#IdentifierExpr", "#NegationExpr",
+                 "#IdentifierExpr"],
+            tags
+        );
+    }
+}
+
+//=====================================================================================================================
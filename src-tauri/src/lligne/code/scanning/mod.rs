@@ -1,5 +1,7 @@
 
+pub mod numeric_literals;
 pub mod scanner;
+pub mod string_literals;
 pub mod token_filters;
 pub mod token_types;
 pub mod tokens;
@@ -0,0 +1,168 @@
+//
+// # Values of Lligne numeric literals.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+
+//=====================================================================================================================
+
+// The reason a numeric literal has no value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NumericLiteralError {
+    // The literal has no digits, has digits not allowed by its radix, or misplaces a '_' separator.
+    Malformed,
+    // The literal is well formed but does not fit in a 64-bit signed integer or a finite 64-bit float.
+    OutOfRange,
+}
+
+//=====================================================================================================================
+
+// Converts the text of an integer literal to its value. Accepts decimal digits or else a "0x", "0o", or "0b" prefix
+// followed by hexadecimal, octal, or binary digits, with single '_' separators allowed between digits. A leading '-'
+// (from a negation folded into the literal) makes the value negative, so that the range is that of an i64.
+pub fn parse_integer_literal(text: &str) -> Result<i64, NumericLiteralError> {
+    let (sign, unsigned_text) = match text.strip_prefix('-') {
+        Some(unsigned_text) => ("-", unsigned_text),
+        None => ("", text),
+    };
+    let (radix, digits) = split_radix_prefix(unsigned_text);
+
+    if !has_separators_between_digits(digits, radix) {
+        return Err(NumericLiteralError::Malformed);
+    }
+
+    let digits = digits.replace('_', "");
+
+    if digits.is_empty() || !digits.chars().all(|ch| ch.is_digit(radix)) {
+        return Err(NumericLiteralError::Malformed);
+    }
+
+    return i64::from_str_radix(&(sign.to_string() + &digits), radix).map_err(|_| NumericLiteralError::OutOfRange);
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Converts the text of a floating point literal, e.g. "1.5", "1.5e-3", or "6E23", to its value, ignoring any '_'
+// separators (allowed singly between digits). A literal such as "1e" is malformed; one such as "1e999" whose value
+// is not a finite 64-bit float is out of range.
+pub fn parse_floating_point_literal(text: &str) -> Result<f64, NumericLiteralError> {
+    if !has_separators_between_digits(text, 10) {
+        return Err(NumericLiteralError::Malformed);
+    }
+
+    let digits = text.replace('_', "");
+
+    if !digits.chars().all(|ch| ch.is_ascii_digit() || matches!(ch, '.' | 'e' | 'E' | '+' | '-')) {
+        return Err(NumericLiteralError::Malformed);
+    }
+
+    let value = digits.parse::<f64>().map_err(|_| NumericLiteralError::Malformed)?;
+
+    if !value.is_finite() {
+        return Err(NumericLiteralError::OutOfRange);
+    }
+
+    return Ok(value);
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Determines whether every '_' separator in some text lies between two digits of the given radix, so that the digits
+// neither start nor end with one and never have two in a row.
+fn has_separators_between_digits(text: &str, radix: u32) -> bool {
+    let is_digit = |byte: Option<&u8>| byte.is_some_and(|&byte| (byte as char).is_digit(radix));
+    let bytes = text.as_bytes();
+
+    return bytes.iter().enumerate().all(|(index, &byte)| {
+        return byte != b'_' || index > 0 && is_digit(bytes.get(index - 1)) && is_digit(bytes.get(index + 1));
+    });
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Separates the radix prefix (if any) of an integer literal from its digits.
+fn split_radix_prefix(text: &str) -> (u32, &str) {
+    let prefix = text.get(..2).unwrap_or("");
+
+    return match prefix {
+        "0x" | "0X" => (16, &text[2..]),
+        "0o" | "0O" => (8, &text[2..]),
+        "0b" | "0B" => (2, &text[2..]),
+        _ => (10, text),
+    };
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_literals() {
+        assert_eq!(Ok(0), parse_integer_literal("0"));
+        assert_eq!(Ok(123), parse_integer_literal("123"));
+        assert_eq!(Ok(1_000_000), parse_integer_literal("1_000_000"));
+        assert_eq!(Ok(255), parse_integer_literal("0xFF"));
+        assert_eq!(Ok(255), parse_integer_literal("0Xf_f"));
+        assert_eq!(Ok(15), parse_integer_literal("0o17"));
+        assert_eq!(Ok(10), parse_integer_literal("0b1010"));
+        assert_eq!(Ok(i64::MAX), parse_integer_literal("9223372036854775807"));
+        assert_eq!(Ok(i64::MAX), parse_integer_literal("0x7FFF_FFFF_FFFF_FFFF"));
+        assert_eq!(Ok(-42), parse_integer_literal("-42"));
+        assert_eq!(Ok(i64::MIN), parse_integer_literal("-9223372036854775808"));
+        assert_eq!(Ok(i64::MIN), parse_integer_literal("-0x8000_0000_0000_0000"));
+    }
+
+    #[test]
+    fn test_malformed_integer_literals() {
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_integer_literal("0x"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_integer_literal("0b_"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_integer_literal("0b102"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_integer_literal("0o8"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_integer_literal("0xFG"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_integer_literal("١٢٣"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_integer_literal("1_"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_integer_literal("1__0"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_integer_literal("0x_1"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_integer_literal("0b1_"));
+    }
+
+    #[test]
+    fn test_out_of_range_integer_literals() {
+        assert_eq!(Err(NumericLiteralError::OutOfRange), parse_integer_literal("9223372036854775808"));
+        assert_eq!(Err(NumericLiteralError::OutOfRange), parse_integer_literal("99999999999999999999"));
+        assert_eq!(Err(NumericLiteralError::OutOfRange), parse_integer_literal("0x8000_0000_0000_0000"));
+        assert_eq!(Err(NumericLiteralError::OutOfRange), parse_integer_literal(&"1".repeat(100)));
+        assert_eq!(Err(NumericLiteralError::OutOfRange), parse_integer_literal("-9223372036854775809"));
+    }
+
+    #[test]
+    fn test_floating_point_literals() {
+        assert_eq!(Ok(1.5), parse_floating_point_literal("1.5"));
+        assert_eq!(Ok(0.0015), parse_floating_point_literal("1.5e-3"));
+        assert_eq!(Ok(6e23), parse_floating_point_literal("6E23"));
+        assert_eq!(Ok(1e10), parse_floating_point_literal("1e+1_0"));
+        assert_eq!(Ok(1000.25), parse_floating_point_literal("1_000.2_5"));
+    }
+
+    #[test]
+    fn test_malformed_floating_point_literals() {
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_floating_point_literal("1e"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_floating_point_literal("1e-"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_floating_point_literal("2.5E+"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_floating_point_literal("1_.5"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_floating_point_literal("1.5e_3"));
+        assert_eq!(Err(NumericLiteralError::Malformed), parse_floating_point_literal("1.5__0"));
+    }
+
+    #[test]
+    fn test_out_of_range_floating_point_literals() {
+        assert_eq!(Err(NumericLiteralError::OutOfRange), parse_floating_point_literal("1e999"));
+        assert_eq!(Err(NumericLiteralError::OutOfRange), parse_floating_point_literal("1.8e308"));
+        assert_eq!(Ok(0.0), parse_floating_point_literal("1e-999"));
+    }
+}
+
+//=====================================================================================================================
@@ -10,22 +10,70 @@
 use std::collections::{HashMap, HashSet};
 use std::str::Chars;
 use cached::proc_macro::once;
+use serde::{Serialize, Serializer};
+use serde::ser::SerializeStruct;
 use shared_vector::Vector;
 use shared_vector::SharedVector;
+use crate::lligne::code::scanning::string_literals::{EscapeSequence, read_escape_sequence};
 use crate::lligne::code::scanning::token_types;
-use crate::lligne::code::scanning::token_types::text_of_token_type;
-use crate::lligne::code::scanning::tokens::Token;
+use crate::lligne::code::scanning::token_types::{tag_of_token_type, text_of_token_type};
+use crate::lligne::code::scanning::tokens::{LongTokenLength, new_token, Token, token_length};
+use crate::lligne::code::util::source_files::{FileId, UNNAMED_FILE_ID};
+use crate::lligne::code::util::source_pos::SourcePos;
 
 pub struct Outcome<'a> {
+    pub file_id: FileId,
     pub source_code: &'a str,
     pub tokens: SharedVector<Token>,
+    pub long_token_lengths: SharedVector<LongTokenLength>,
     pub new_line_offsets: SharedVector<u32>,
 }
 
+//---------------------------------------------------------------------------------------------------------------------
+
+// In JSON a scan outcome is {"tokens": [...], "newLineOffsets": [...]}, each token as for Token but with its true
+// length even if it is a long token, e.g.
+//
+//   {"tokens":[{"sourceOffset":0,"sourceLength":1,"tokenType":"#TokenTypeIdentifier"}, ...],"newLineOffsets":[]}
+impl<'a> Serialize for Outcome<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let tokens: Vec<TokenJson> = self.tokens.iter()
+            .map(|token| TokenJson {
+                source_offset: token.source_offset,
+                source_length: token_length(*token, &self.long_token_lengths),
+                token_type: tag_of_token_type(token.token_type),
+            })
+            .collect();
+
+        let mut state = serializer.serialize_struct("Outcome", 2)?;
+        state.serialize_field("tokens", &tokens)?;
+        state.serialize_field("newLineOffsets", self.new_line_offsets.as_slice())?;
+        return state.end();
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// The JSON of one token of a scan outcome.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenJson {
+    source_offset: u32,
+    source_length: u32,
+    token_type: &'static str,
+}
+
 //=====================================================================================================================
 
 // Converts the given source code to an array of tokens plus an array of new line character offsets.
 pub fn scan(source_code: &str) -> Outcome {
+    return scan_file(UNNAMED_FILE_ID, source_code);
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Scans the source code of the given file from a source database.
+pub fn scan_file(file_id: FileId, source_code: &str) -> Outcome<'_> {
 
     // Create a scanner.
     let mut scanner: Scanner = Scanner::new(source_code);
@@ -35,12 +83,115 @@ pub fn scan(source_code: &str) -> Outcome {
 
     // Extract the results.
     return Outcome {
+        file_id,
         source_code,
         tokens: scanner.tokens.into_shared(),
+        long_token_lengths: scanner.long_token_lengths.into_shared(),
         new_line_offsets: scanner.new_line_offsets.into_shared(),
     };
 }
 
+//---------------------------------------------------------------------------------------------------------------------
+
+// Rescans source code after an edit that replaced the text at `replaced` (offsets into the previously scanned source
+// code) to give `source_code`. Only the edited region is scanned again: scanning restarts after the last token that
+// the edit cannot affect (allowing for the two characters of lookahead) and stops as soon as a token starts where a
+// token after the edit started before. From there on the previous tokens, long token lengths, and line feed offsets
+// are reused, shifted by the change in length. The outcome is the same as from scanning all of `source_code`.
+pub fn rescan<'a>(previous: &Outcome, replaced: SourcePos, source_code: &'a str) -> Outcome<'a> {
+    let length_change = source_code.len() as i64 - previous.source_code.len() as i64;
+    let shift = |offset: u32| (offset as i64 + length_change) as u32;
+
+    let end_of = |token: &Token| token.source_offset + token_length(*token, &previous.long_token_lengths);
+
+    // Keep the tokens ending at least two characters before the edit.
+    let kept_count = previous.tokens.partition_point(|token| {
+        let end = end_of(token);
+        return token.token_type != token_types::EOF &&
+            end <= replaced.start_offset &&
+            previous.source_code[end as usize..replaced.start_offset as usize].chars().nth(1).is_some();
+    });
+    let restart_offset = if kept_count > 0 { end_of(&previous.tokens[kept_count - 1]) } else { 0 };
+
+    let mut scanner: Scanner = Scanner::new_at(source_code, restart_offset);
+    for token in previous.tokens[..kept_count].iter() {
+        scanner.tokens.push(*token);
+    }
+    for long_token_length in previous.long_token_lengths.iter().filter(|l| l.source_offset < restart_offset) {
+        scanner.long_token_lengths.push(*long_token_length);
+    }
+    for new_line_offset in previous.new_line_offsets.iter().filter(|&&offset| offset < restart_offset) {
+        scanner.new_line_offsets.push(*new_line_offset);
+    }
+
+    // Scan until resynchronized with the previous tokens after the edit or else to the end.
+    let edit_end_offset = shift(replaced.end_offset);
+    let mut reused_from: Option<(u32, usize)> = None;
+
+    loop {
+        let token = scanner.read_token();
+
+        if token.source_offset >= edit_end_offset {
+            let previous_offset = shift_back(token.source_offset, length_change);
+            let index = previous.tokens.partition_point(|previous| previous.source_offset < previous_offset);
+
+            if index < previous.tokens.len() && previous.tokens[index].source_offset == previous_offset {
+                reused_from = Some((token.source_offset, index));
+                break;
+            }
+        }
+
+        if token.token_type == token_types::EOF {
+            scanner.tokens.push(token);
+            scanner.tokens.push(token);
+            scanner.tokens.push(token);
+            break;
+        }
+
+        scanner.tokens.push(token);
+    }
+
+    let mut tokens = scanner.tokens;
+    let mut long_token_lengths = scanner.long_token_lengths;
+    let mut new_line_offsets = scanner.new_line_offsets;
+
+    if let Some((offset, index)) = reused_from {
+        let previous_offset = shift_back(offset, length_change);
+
+        for token in previous.tokens[index..].iter() {
+            tokens.push(Token { source_offset: shift(token.source_offset), ..*token });
+        }
+
+        long_token_lengths.retain(|long_token_length| long_token_length.source_offset < offset);
+        for long_token_length in previous.long_token_lengths.iter().filter(|l| l.source_offset >= previous_offset) {
+            long_token_lengths.push(LongTokenLength {
+                source_offset: shift(long_token_length.source_offset),
+                source_length: long_token_length.source_length,
+            });
+        }
+
+        new_line_offsets.retain(|&new_line_offset| new_line_offset < offset);
+        for new_line_offset in previous.new_line_offsets.iter().filter(|&&o| o >= previous_offset) {
+            new_line_offsets.push(shift(*new_line_offset));
+        }
+    }
+
+    return Outcome {
+        file_id: previous.file_id,
+        source_code,
+        tokens: tokens.into_shared(),
+        long_token_lengths: long_token_lengths.into_shared(),
+        new_line_offsets: new_line_offsets.into_shared(),
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Converts an offset after an edit back to the corresponding offset before it.
+fn shift_back(offset: u32, length_change: i64) -> u32 {
+    return (offset as i64 - length_change) as u32;
+}
+
 //=====================================================================================================================
 
 // Converts a string of Lligne source code into tokens.
@@ -52,6 +203,7 @@ struct Scanner<'a> {
     char_ahead_1: char,
     char_ahead_2: char,
     tokens: Vector<Token>,
+    long_token_lengths: Vector<LongTokenLength>,
     new_line_offsets: Vector<u32>,
 }
 
@@ -60,7 +212,14 @@ impl<'a> Scanner<'a> {
 
     // Allocates a new scanner for given source_code.
     fn new(source_code: &'a str) -> Self {
-        let mut source_code_iter = source_code.chars();
+        return Scanner::new_at(source_code, 0);
+    }
+
+//---------------------------------------------------------------------------------------------------------------------
+
+    // Allocates a new scanner for given source_code that starts at the given offset (a boundary between tokens).
+    fn new_at(source_code: &'a str, offset: u32) -> Self {
+        let mut source_code_iter = source_code[offset as usize..].chars();
         let char_ahead_1 = source_code_iter.next();
         let char_ahead_2 = source_code_iter.next();
 
@@ -68,12 +227,13 @@ impl<'a> Scanner<'a> {
         return Scanner {
             source_code,
             source_code_iter,
-            marked_pos: 0,
-            current_pos: 0,
+            marked_pos: offset,
+            current_pos: offset,
             char_ahead_1: char_ahead_1.unwrap_or('\0'),
             char_ahead_2: char_ahead_2.unwrap_or('\0'),
             new_line_offsets: Vector::new(),
             tokens: Vector::new(),
+            long_token_lengths: Vector::new(),
         };
     }
 
@@ -117,7 +277,7 @@ impl<'a> Scanner<'a> {
             return self.scan_identifier_or_keyword();
         }
         if is_digit(ch) {
-            return self.scan_number();
+            return self.scan_number(ch);
         }
 
         // Handle individual characters.
@@ -143,8 +303,8 @@ impl<'a> Scanner<'a> {
             ')' => self.token(token_types::RIGHT_PARENTHESIS),
             ';' => self.token(token_types::SEMICOLON),
             '/' => self.scan_after_slash(),
-            '"' => self.scan_double_quoted_string(),
-            '\'' => self.scan_single_quoted_string(),
+            '"' => self.scan_quoted_string('"', token_types::DOUBLE_QUOTED_STRING, token_types::UNCLOSED_DOUBLE_QUOTED_STRING),
+            '\'' => self.scan_quoted_string('\'', token_types::SINGLE_QUOTED_STRING, token_types::UNCLOSED_SINGLE_QUOTED_STRING),
             '|' => self.token(token_types::VERTICAL_BAR),
             '\0' => self.eof_token(),
             _ => self.token(token_types::UNRECOGNIZED_CHAR),
@@ -165,6 +325,15 @@ impl<'a> Scanner<'a> {
         self.char_ahead_2 = self.source_code_iter.next().unwrap_or('\0');
     }
 
+//---------------------------------------------------------------------------------------------------------------------
+
+    // Consumes characters until reaching the given offset (which must not lie beyond the end of the current line).
+    fn advance_past(&mut self, offset: u32) {
+        while self.current_pos < offset {
+            self.advance();
+        }
+    }
+
 //---------------------------------------------------------------------------------------------------------------------
 
     // Builds a new EOF token for the marked position.
@@ -305,11 +474,7 @@ impl<'a> Scanner<'a> {
             self.advance();
         }
 
-        return Token {
-            source_offset: mark,
-            source_length: (self.current_pos - mark) as u16,
-            token_type: token_types::BACK_TICKED_STRING,
-        };
+        return self.new_token(mark, self.current_pos - mark, token_types::BACK_TICKED_STRING);
     }
 
 //---------------------------------------------------------------------------------------------------------------------
@@ -348,34 +513,7 @@ impl<'a> Scanner<'a> {
             self.advance();
         }
 
-        return Token {
-            source_offset: mark,
-            source_length: (self.current_pos - mark) as u16,
-            token_type: token_types::DOCUMENTATION,
-        };
-    }
-
-//---------------------------------------------------------------------------------------------------------------------
-
-    // Scans the remainder of a string literal after the initial double quote character has been consumed.
-    fn scan_double_quoted_string(&mut self) -> Token {
-        loop {
-            if self.char_ahead_1 == '"' {
-                self.advance();
-                return self.token(token_types::DOUBLE_QUOTED_STRING);
-            }
-
-            if self.char_ahead_1 == '\\' {
-                self.advance();
-                // TODO: handle escape sequences properly
-                self.advance();
-            }
-
-            if self.char_ahead_1 == '\n' {
-                return self.token(token_types::UNCLOSED_DOUBLE_QUOTED_STRING);
-            }
-            self.advance()
-        }
+        return self.new_token(mark, self.current_pos - mark, token_types::DOCUMENTATION);
     }
 
 //---------------------------------------------------------------------------------------------------------------------
@@ -392,90 +530,146 @@ impl<'a> Scanner<'a> {
         let keywords = keywords();
         let keyword_token_type = keywords.get(&text);
         match keyword_token_type {
-            Some(token_type) => return Token {
-                source_offset: self.marked_pos,
-                source_length: (self.current_pos - self.marked_pos) as u16,
-                token_type: *token_type,
-            },
+            Some(token_type) => return self.token(*token_type),
             _ => {}
         }
 
         if built_in_types().contains(text) {
-            return Token {
-                source_offset: self.marked_pos,
-                source_length: (self.current_pos - self.marked_pos) as u16,
-                token_type: token_types::BUILT_IN_TYPE,
-            };
+            return self.token(token_types::BUILT_IN_TYPE);
         }
 
-        return Token {
-            source_offset: self.marked_pos,
-            source_length: (self.current_pos - self.marked_pos) as u16,
-            token_type: token_types::IDENTIFIER,
-        };
+        return self.token(token_types::IDENTIFIER);
     }
 
 //---------------------------------------------------------------------------------------------------------------------
 
     // Scans a numeric literal after the opening digit has been consumed.
-    fn scan_number(&mut self) -> Token {
-        while is_digit(self.char_ahead_1) {
+    fn scan_number(&mut self, first_digit: char) -> Token {
+        if first_digit == '0' && matches!(self.char_ahead_1, 'x' | 'X' | 'o' | 'O' | 'b' | 'B') {
             self.advance();
+            return self.scan_number_with_radix_prefix();
         }
 
+        self.scan_digits();
+
         if self.char_ahead_1 == '.' && is_digit(self.char_ahead_2) {
             self.advance();
             return self.scan_number_floating_point();
         }
 
+        if self.char_ahead_1 == 'e' || self.char_ahead_1 == 'E' {
+            return self.scan_number_exponent();
+        }
+
         return self.token(token_types::INTEGER_LITERAL);
     }
 
+//---------------------------------------------------------------------------------------------------------------------
+
+    // Consumes a run of digits and '_' separators.
+    fn scan_digits(&mut self) {
+        while is_digit(self.char_ahead_1) || self.char_ahead_1 == '_' {
+            self.advance();
+        }
+    }
+
 //---------------------------------------------------------------------------------------------------------------------
 
     // Scans a floating point literal after the decimal point has been consumed.
     fn scan_number_floating_point(&mut self) -> Token {
-        while is_digit(self.char_ahead_1) {
+        self.scan_digits();
+
+        if self.char_ahead_1 == 'e' || self.char_ahead_1 == 'E' {
+            return self.scan_number_exponent();
+        }
+
+        return self.token(token_types::FLOATING_POINT_LITERAL);
+    }
+
+//---------------------------------------------------------------------------------------------------------------------
+
+    // Scans the exponent of a floating point literal starting with the 'e' or 'E'. The exponent digits may be missing,
+    // in which case the malformed literal is reported by the parser.
+    fn scan_number_exponent(&mut self) -> Token {
+        self.advance();
+
+        if self.char_ahead_1 == '+' || self.char_ahead_1 == '-' {
             self.advance();
         }
 
-        // TODO: exponents
+        self.scan_digits();
 
         return self.token(token_types::FLOATING_POINT_LITERAL);
     }
 
 //---------------------------------------------------------------------------------------------------------------------
 
-    // Scans the remainder of a string literal after the initial single quote character has been consumed.
-    fn scan_single_quoted_string(&mut self) -> Token {
+    // Scans a hexadecimal, octal, or binary integer literal after its "0x", "0o", or "0b" prefix has been consumed.
+    // Any letters and digits that follow are taken as part of the literal so that the parser can report bad digits.
+    fn scan_number_with_radix_prefix(&mut self) -> Token {
+        while self.char_ahead_1.is_ascii_alphanumeric() || self.char_ahead_1 == '_' {
+            self.advance();
+        }
+
+        return self.token(token_types::INTEGER_LITERAL);
+    }
+
+//---------------------------------------------------------------------------------------------------------------------
+
+    // Scans the remainder of a string or character literal after the opening delimiter has been consumed. A literal
+    // with invalid escape sequences becomes one INVALID_STRING_LITERAL token covering the whole literal; the parser
+    // reports the offending escape sequences.
+    fn scan_quoted_string(&mut self, delimiter: char, token_type: u16, unclosed_token_type: u16) -> Token {
+        let mut has_invalid_escapes = false;
+
         loop {
-            if self.char_ahead_1 == '\'' {
+            if self.char_ahead_1 == delimiter {
                 self.advance();
-                return self.token(token_types::SINGLE_QUOTED_STRING);
+                break;
             }
 
-            if self.char_ahead_1 == '\\' {
-                self.advance();
-                // TODO: handle escape sequences properly
-                self.advance();
+            if self.char_ahead_1 == '\n' || self.char_ahead_1 == '\0' {
+                return self.token(unclosed_token_type);
             }
 
-            if self.char_ahead_1 == '\n' {
-                return self.token(token_types::UNCLOSED_SINGLE_QUOTED_STRING);
+            if self.char_ahead_1 == '\\' {
+                let escape_offset = self.current_pos;
+
+                match read_escape_sequence(&self.source_code[escape_offset as usize..]) {
+                    EscapeSequence::Valid { length, .. } => {
+                        self.advance_past(escape_offset + length as u32);
+                    }
+                    EscapeSequence::Invalid { length } => {
+                        self.advance_past(escape_offset + length as u32);
+                        has_invalid_escapes = true;
+                    }
+                }
+
+                continue;
             }
+
             self.advance()
         }
+
+        if has_invalid_escapes {
+            return self.token(token_types::INVALID_STRING_LITERAL);
+        }
+
+        return self.token(token_type);
+    }
+
+//---------------------------------------------------------------------------------------------------------------------
+
+    // Builds a new token of given type, recording its length in the side table if it is a long token.
+    fn new_token(&mut self, source_offset: u32, source_length: u32, token_type: u16) -> Token {
+        return new_token(source_offset, source_length, token_type, &mut self.long_token_lengths);
     }
 
 //---------------------------------------------------------------------------------------------------------------------
 
     // Builds a new token of given type with text from the marked position to the current position.
     fn token(&mut self, token_type: u16) -> Token {
-        return Token {
-            source_offset: self.marked_pos,
-            source_length: (self.current_pos - self.marked_pos) as u16,
-            token_type,
-        };
+        return self.new_token(self.marked_pos, self.current_pos - self.marked_pos, token_type);
     }
 
 //---------------------------------------------------------------------------------------------------------------------
@@ -541,6 +735,7 @@ fn keywords() -> HashMap<&'static str, u16> {
 
 #[cfg(test)]
 mod tests {
+    use crate::lligne::code::scanning::tokens::{LONG_TOKEN_LENGTH, token_length};
     use super::*;
 
     fn expect_token(
@@ -679,6 +874,53 @@ mod tests {
         expect_token(&outcome, 8, token_types::EOF, 19, 0);
     }
 
+    #[test]
+    fn test_long_tokens() {
+        let documentation = "// generated documentation line\n".repeat(3000);
+        let string_block = format!("`{}\n", "x".repeat(70000));
+        let source_code = format!("{}a {}b", documentation, string_block);
+        let outcome = scan(&source_code);
+
+        assert_eq!(7, outcome.tokens.len());
+        expect_token(&outcome, 0, token_types::DOCUMENTATION, 0, LONG_TOKEN_LENGTH);
+        expect_token(&outcome, 1, token_types::IDENTIFIER, 96000, 1);
+        expect_token(&outcome, 2, token_types::BACK_TICKED_STRING, 96002, LONG_TOKEN_LENGTH);
+        expect_token(&outcome, 3, token_types::IDENTIFIER, 166004, 1);
+
+        assert_eq!(96000, token_length(outcome.tokens[0], &outcome.long_token_lengths));
+        assert_eq!(70002, token_length(outcome.tokens[2], &outcome.long_token_lengths));
+        assert_eq!(2, outcome.long_token_lengths.len());
+    }
+
+    #[test]
+    fn test_number_formats() {
+        let outcome = scan("1.5e-3 6E23 0xFF 0o17 0b1010 1_000_000 2e+8 1.0..2");
+        assert_eq!(13, outcome.tokens.len());
+        expect_token(&outcome, 0, token_types::FLOATING_POINT_LITERAL, 0, 6);
+        expect_token(&outcome, 1, token_types::FLOATING_POINT_LITERAL, 7, 4);
+        expect_token(&outcome, 2, token_types::INTEGER_LITERAL, 12, 4);
+        expect_token(&outcome, 3, token_types::INTEGER_LITERAL, 17, 4);
+        expect_token(&outcome, 4, token_types::INTEGER_LITERAL, 22, 6);
+        expect_token(&outcome, 5, token_types::INTEGER_LITERAL, 29, 9);
+        expect_token(&outcome, 6, token_types::FLOATING_POINT_LITERAL, 39, 4);
+        expect_token(&outcome, 7, token_types::FLOATING_POINT_LITERAL, 44, 3);
+        expect_token(&outcome, 8, token_types::DOT_DOT, 47, 2);
+        expect_token(&outcome, 9, token_types::INTEGER_LITERAL, 49, 1);
+        expect_token(&outcome, 10, token_types::EOF, 50, 0);
+    }
+
+    #[test]
+    fn test_malformed_number_formats() {
+        let outcome = scan("0x 1e 0b12 3e-)");
+        assert_eq!(8, outcome.tokens.len());
+        expect_token(&outcome, 0, token_types::INTEGER_LITERAL, 0, 2);
+        expect_token(&outcome, 1, token_types::FLOATING_POINT_LITERAL, 3, 2);
+        expect_token(&outcome, 2, token_types::INTEGER_LITERAL, 6, 4);
+        expect_token(&outcome, 3, token_types::FLOATING_POINT_LITERAL, 11, 3);
+        expect_token(&outcome, 4, token_types::RIGHT_PARENTHESIS, 14, 1);
+        expect_token(&outcome, 5, token_types::EOF, 15, 0);
+    }
+
     #[test]
     fn test_double_quoted_strings() {
         let outcome = scan(
@@ -713,6 +955,42 @@ mod tests {
         expect_token(&outcome, 6, token_types::EOF, 30, 0);
     }
 
+    #[test]
+    fn test_unclosed_strings_at_end_of_file() {
+        let outcome = scan(r#""abc 'x\"#);
+        assert_eq!(4, outcome.tokens.len());
+        expect_token(&outcome, 0, token_types::UNCLOSED_DOUBLE_QUOTED_STRING, 0, 8);
+        expect_token(&outcome, 1, token_types::EOF, 8, 0);
+
+        let outcome = scan("'\\'");
+        assert_eq!(4, outcome.tokens.len());
+        expect_token(&outcome, 0, token_types::UNCLOSED_SINGLE_QUOTED_STRING, 0, 3);
+    }
+
+    #[test]
+    fn test_escape_sequences() {
+        let outcome = scan(r#""a\tb\n\"\\" '\'' '\u{1F600}' "\x41\r\0""#);
+        assert_eq!(0, outcome.new_line_offsets.len());
+        assert_eq!(7, outcome.tokens.len());
+        expect_token(&outcome, 0, token_types::DOUBLE_QUOTED_STRING, 0, 12);
+        expect_token(&outcome, 1, token_types::SINGLE_QUOTED_STRING, 13, 4);
+        expect_token(&outcome, 2, token_types::SINGLE_QUOTED_STRING, 18, 11);
+        expect_token(&outcome, 3, token_types::DOUBLE_QUOTED_STRING, 30, 10);
+        expect_token(&outcome, 4, token_types::EOF, 40, 0);
+    }
+
+    #[test]
+    fn test_invalid_escape_sequences() {
+        let outcome = scan(r#"x "a\qb\u{zz}c" 'ok' "\x80" y"#);
+        assert_eq!(8, outcome.tokens.len());
+        expect_token(&outcome, 0, token_types::IDENTIFIER, 0, 1);
+        expect_token(&outcome, 1, token_types::INVALID_STRING_LITERAL, 2, 13);
+        expect_token(&outcome, 2, token_types::SINGLE_QUOTED_STRING, 16, 4);
+        expect_token(&outcome, 3, token_types::INVALID_STRING_LITERAL, 21, 6);
+        expect_token(&outcome, 4, token_types::IDENTIFIER, 28, 1);
+        expect_token(&outcome, 5, token_types::EOF, 29, 0);
+    }
+
     #[test]
     fn test_each_fixed_text_token() {
         for token_type in 1..token_types::WHERE {
@@ -779,8 +1057,95 @@ mod tests {
         expect_token(&outcome, 5, token_types::EOF, 25, 0);
         expect_token(&outcome, 6, token_types::EOF, 25, 0);
     }
-}
 
-//=====================================================================================================================
+    fn check_rescan(old_source_code: &str, start_offset: usize, end_offset: usize, new_text: &str) {
+        let source_code = [&old_source_code[..start_offset], new_text, &old_source_code[end_offset..]].concat();
+        let replaced = SourcePos { start_offset: start_offset as u32, end_offset: end_offset as u32 };
 
+        let outcome = rescan(&scan(old_source_code), replaced, &source_code);
+        let expected = scan(&source_code);
 
+        let summary = |outcome: &Outcome| -> Vec<(u32, u32, u16)> {
+            return outcome.tokens.iter()
+                .map(|token| (token.source_offset, token_length(*token, &outcome.long_token_lengths), token.token_type))
+                .collect();
+        };
+
+        let message = format!("Replacing {start_offset}..{end_offset} of {old_source_code:?} by {new_text:?}");
+        assert_eq!(summary(&expected), summary(&outcome), "{message}");
+        assert_eq!(&expected.long_token_lengths[..], &outcome.long_token_lengths[..], "{message}");
+        assert_eq!(&expected.new_line_offsets[..], &outcome.new_line_offsets[..], "{message}");
+    }
+
+    #[test]
+    fn test_rescan() {
+        check_rescan("abc + def", 3, 3, "x");
+        check_rescan("abc + def", 0, 0, "z ");
+        check_rescan("abc + def", 9, 9, " * 2");
+        check_rescan("abc + def", 0, 9, "");
+        check_rescan("", 0, 0, "x: 1");
+        check_rescan("a- b", 3, 3, "x");
+        check_rescan("a-  b", 2, 4, "");
+        check_rescan("x: 1,\ny: 2,\nz: 3", 5, 6, "");
+        check_rescan("x: 1,\ny: 2,\nz: 3", 9, 9, "\n\n");
+        check_rescan("x: 'a',\ny: 'b'", 5, 5, "'");
+        check_rescan(r#"x "a\qb" y "c\u{zz}d" z"#, 5, 5, "\\");
+        check_rescan(r#"x "a\qb" y "c\u{zz}d" z"#, 4, 5, "");
+        check_rescan("// one\n\n// two\nx", 7, 8, "");
+        check_rescan("// one\n// two\nx", 6, 7, " ");
+        check_rescan("`one\n  `two\n\n  `three\nx", 9, 10, "");
+        check_rescan("s = 'größe' + \"‽\"", 7, 9, "ö");
+        check_rescan("s = 'größe' + \"‽\"", 16, 16, "‽‽");
+    }
+
+    #[test]
+    fn test_rescan_long_tokens() {
+        let documentation = "// ".to_string() + &"long ".repeat(20000) + "\n";
+        let source_code = documentation.clone() + "x: 1,\n" + &documentation + "y: 2\n";
+
+        check_rescan(&source_code, documentation.len() + 1, documentation.len() + 1, "yz");
+        check_rescan(&source_code, documentation.len() - 1, documentation.len(), "");
+        check_rescan(&source_code, 10, 20, "");
+    }
+
+    #[test]
+    fn test_rescan_every_edit() {
+        let source_code = "// doc\nf(x: 'a\\q', y: `b\n  `c\n) -> a-b <= 3.5e1 ?: \"ü\" // end\n";
+        let new_texts = ["", " ", "\n", "-", "x", "/", "`", "'", "\\", "1"];
+
+        let boundaries: Vec<usize> = (0..=source_code.len())
+            .filter(|&offset| source_code.is_char_boundary(offset))
+            .collect();
+
+        for (index, &start_offset) in boundaries.iter().enumerate() {
+            for &end_offset in boundaries[index..].iter().take(3) {
+                for new_text in new_texts {
+                    check_rescan(source_code, start_offset, end_offset, new_text);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_outcome_json() {
+        let outcome = scan("x:\n1");
+
+        assert_eq!(
+            concat!(
+                r##"{"tokens":[{"sourceOffset":0,"sourceLength":1,"tokenType":"#TokenTypeIdentifier"},"##,
+                r##"{"sourceOffset":1,"sourceLength":1,"tokenType":"#TokenTypeColon"},"##,
+                r##"{"sourceOffset":3,"sourceLength":1,"tokenType":"#TokenTypeIntegerLiteral"},"##,
+                r##"{"sourceOffset":4,"sourceLength":0,"tokenType":"#TokenTypeEof"},"##,
+                r##"{"sourceOffset":4,"sourceLength":0,"tokenType":"#TokenTypeEof"},"##,
+                r##"{"sourceOffset":4,"sourceLength":0,"tokenType":"#TokenTypeEof"}],"newLineOffsets":[2]}"##,
+            ),
+            serde_json::to_string(&outcome).unwrap()
+        );
+
+        let source_code = format!("`{}", "long ".repeat(20000));
+        let json = serde_json::to_value(scan(&source_code)).unwrap();
+        assert_eq!(100001, json["tokens"][0]["sourceLength"]);
+    }
+}
+
+//=====================================================================================================================
@@ -0,0 +1,251 @@
+//
+// # Escape sequences and decoded values of Lligne string literals.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+
+//=====================================================================================================================
+
+// The result of reading one escape sequence. Lengths are in bytes and include the leading backslash.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EscapeSequence {
+    Valid { value: char, length: usize },
+    Invalid { length: usize },
+}
+
+//=====================================================================================================================
+
+// Reads the escape sequence at the start of the given text, which must begin with a backslash. Recognizes
+// \n \t \r \\ \" \' \0, \xHH (ASCII only), and \u{H...} (one to six hex digits). An invalid escape sequence
+// extends as far as the characters that could have belonged to it, but never past the end of the line.
+pub fn read_escape_sequence(text: &str) -> EscapeSequence {
+    let mut chars = text[1..].chars();
+
+    let ch = match chars.next() {
+        Some(ch) if ch != '\n' => ch,
+        _ => return EscapeSequence::Invalid { length: 1 },
+    };
+
+    let simple_value = match ch {
+        'n' => Some('\n'),
+        't' => Some('\t'),
+        'r' => Some('\r'),
+        '\\' => Some('\\'),
+        '"' => Some('"'),
+        '\'' => Some('\''),
+        '0' => Some('\0'),
+        _ => None,
+    };
+
+    if let Some(value) = simple_value {
+        return EscapeSequence::Valid { value, length: 2 };
+    }
+
+    return match ch {
+        'x' => read_hex_escape_sequence(&text[2..]),
+        'u' => read_unicode_escape_sequence(&text[2..]),
+        _ => EscapeSequence::Invalid { length: 1 + ch.len_utf8() },
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Reads the two hex digits of a \xHH escape sequence after the "\x" has been consumed.
+fn read_hex_escape_sequence(text: &str) -> EscapeSequence {
+    let digit_count = count_hex_digits(text, 2);
+
+    if digit_count == 2 {
+        let value = u32::from_str_radix(&text[..2], 16).unwrap();
+        if value <= 0x7F {
+            return EscapeSequence::Valid { value: char::from_u32(value).unwrap(), length: 4 };
+        }
+    }
+
+    return EscapeSequence::Invalid { length: 2 + digit_count };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Reads the braced hex digits of a \u{H...} escape sequence after the "\u" has been consumed.
+fn read_unicode_escape_sequence(text: &str) -> EscapeSequence {
+    if !text.starts_with('{') {
+        return EscapeSequence::Invalid { length: 2 };
+    }
+
+    let digit_count = count_hex_digits(&text[1..], usize::MAX);
+
+    if !text[1 + digit_count..].starts_with('}') {
+        return EscapeSequence::Invalid { length: 3 + digit_count };
+    }
+
+    let length = 4 + digit_count;
+
+    if digit_count == 0 || digit_count > 6 {
+        return EscapeSequence::Invalid { length };
+    }
+
+    let value = u32::from_str_radix(&text[1..1 + digit_count], 16).unwrap();
+
+    return match char::from_u32(value) {
+        Some(value) => EscapeSequence::Valid { value, length },
+        None => EscapeSequence::Invalid { length },
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Counts the (ASCII) hex digits at the start of the given text, up to a maximum.
+fn count_hex_digits(text: &str, max_count: usize) -> usize {
+    return text.bytes().take(max_count).take_while(|b| b.is_ascii_hexdigit()).count();
+}
+
+//=====================================================================================================================
+
+// Replaces the escape sequences in the given text with the characters they stand for. Invalid escape sequences
+// (already reported by the scanner) are kept verbatim.
+pub fn decode_escape_sequences(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut index = 0;
+
+    while let Some(offset) = text[index..].find('\\') {
+        result.push_str(&text[index..index + offset]);
+        index += offset;
+
+        match read_escape_sequence(&text[index..]) {
+            EscapeSequence::Valid { value, length } => {
+                result.push(value);
+                index += length;
+            }
+            EscapeSequence::Invalid { length } => {
+                result.push_str(&text[index..index + length]);
+                index += length;
+            }
+        }
+    }
+
+    result.push_str(&text[index..]);
+
+    return result;
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Decodes the value of a single or double quoted string literal given its full text including the delimiters.
+pub fn decode_quoted_string(text: &str) -> String {
+    return decode_escape_sequences(&text[1..text.len() - 1]);
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Decodes the value of a block of lines each introduced by the given delimiter, e.g. a back-ticked string. The
+// indentation and delimiter opening each line are stripped, but trailing white space is part of the value; the lines
+// are joined by line feeds (a carriage return ending a line is dropped). Any text not introduced by the delimiter
+// (e.g. the white space after the final line) is ignored.
+pub fn decode_string_block(text: &str, delimiter: char, decodes_escapes: bool) -> String {
+    let lines: Vec<&str> = text.lines()
+        .map(|line| line.trim_start())
+        .filter(|line| line.starts_with(delimiter))
+        .map(|line| &line[delimiter.len_utf8()..])
+        .collect();
+
+    let result = lines.join("\n");
+
+    if decodes_escapes {
+        return decode_escape_sequences(&result);
+    }
+
+    return result;
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expect_valid(text: &str, expected_value: char, expected_length: usize) {
+        assert_eq!(
+            EscapeSequence::Valid { value: expected_value, length: expected_length },
+            read_escape_sequence(text),
+            "Wrong escape sequence for '{}'", text
+        );
+    }
+
+    fn expect_invalid(text: &str, expected_length: usize) {
+        assert_eq!(
+            EscapeSequence::Invalid { length: expected_length },
+            read_escape_sequence(text),
+            "Wrong escape sequence for '{}'", text
+        );
+    }
+
+    #[test]
+    fn test_simple_escape_sequences() {
+        expect_valid("\\n", '\n', 2);
+        expect_valid("\\t", '\t', 2);
+        expect_valid("\\r", '\r', 2);
+        expect_valid("\\\\", '\\', 2);
+        expect_valid("\\\"abc", '"', 2);
+        expect_valid("\\'", '\'', 2);
+        expect_valid("\\0", '\0', 2);
+    }
+
+    #[test]
+    fn test_hex_escape_sequences() {
+        expect_valid("\\x41", 'A', 4);
+        expect_valid("\\x7f", '\x7F', 4);
+        expect_valid("\\x0a0", '\n', 4);
+        expect_invalid("\\x80", 4);
+        expect_invalid("\\x4", 3);
+        expect_invalid("\\x\"", 2);
+    }
+
+    #[test]
+    fn test_unicode_escape_sequences() {
+        expect_valid("\\u{41}", 'A', 6);
+        expect_valid("\\u{1F600}", '😀', 9);
+        expect_valid("\\u{10FFFF}", '\u{10FFFF}', 10);
+        expect_invalid("\\u{}", 4);
+        expect_invalid("\\u{110000}", 10);
+        expect_invalid("\\u{D800}", 8);
+        expect_invalid("\\u{1234567}", 11);
+        expect_invalid("\\u{12\"", 5);
+        expect_invalid("\\u{zz}", 3);
+        expect_invalid("\\u41", 2);
+    }
+
+    #[test]
+    fn test_invalid_escape_sequences() {
+        expect_invalid("\\q", 2);
+        expect_invalid("\\ß", 3);
+        expect_invalid("\\", 1);
+        expect_invalid("\\\nabc", 1);
+    }
+
+    #[test]
+    fn test_decode_quoted_string() {
+        assert_eq!("abc", decode_quoted_string("\"abc\""));
+        assert_eq!("", decode_quoted_string("''"));
+        assert_eq!("a\tb\nc\\d\"e'f", decode_quoted_string(r#""a\tb\nc\\d\"e\'f""#));
+        assert_eq!("größe 😀!", decode_quoted_string(r#"'gr\u{f6}\u{DF}e \u{1f600}\x21'"#));
+        assert_eq!("x\\qy", decode_quoted_string(r#""x\qy""#));
+    }
+
+    #[test]
+    fn test_decode_string_block() {
+        assert_eq!("abc 123", decode_string_block("`abc 123\n", '`', false));
+        assert_eq!("abc 123", decode_string_block("`abc 123", '`', false));
+        assert_eq!(
+            "abc 123\n  - one  \n  - two",
+            decode_string_block("`abc 123\n`  - one  \r\n  `  - two\n\n  ", '`', false)
+        );
+        assert_eq!("", decode_string_block("`\n", '`', false));
+        assert_eq!("a\\tb\n", decode_string_block("`a\\tb\n  `\n", '`', false));
+        assert_eq!("a\tb\n\"c\"", decode_string_block("\"a\\tb\n  \"\\\"c\\\"", '"', true));
+        assert_eq!("one\ntwo", decode_string_block("'one\n    'two", '\'', true));
+        assert_eq!("trailing \t\n  spaces  ", decode_string_block("`trailing \t\n   `  spaces  \n  ", '`', false));
+    }
+}
+
+//=====================================================================================================================
@@ -85,8 +85,10 @@ pub fn filter(scan_result: scanner::Outcome) -> scanner::Outcome {
     }
 
     return scanner::Outcome {
+        file_id: scan_result.file_id,
         source_code: scan_result.source_code,
         tokens: result.into_shared(),
+        long_token_lengths: scan_result.long_token_lengths,
         new_line_offsets: scan_result.new_line_offsets,
     };
 }
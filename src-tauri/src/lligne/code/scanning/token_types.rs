@@ -73,16 +73,17 @@ pub const SINGLE_QUOTED_STRING: u16 = 50;
 pub const IDENTIFIER: u16 = 51;
 
 // Errors
-pub const UNCLOSED_DOUBLE_QUOTED_STRING: u16 = 52;
-pub const UNCLOSED_SINGLE_QUOTED_STRING: u16 = 53;
-pub const UNRECOGNIZED_CHAR: u16 = 54;
+pub const INVALID_STRING_LITERAL: u16 = 52;
+pub const UNCLOSED_DOUBLE_QUOTED_STRING: u16 = 53;
+pub const UNCLOSED_SINGLE_QUOTED_STRING: u16 = 54;
+pub const UNRECOGNIZED_CHAR: u16 = 55;
 
 // Synthetic token types from postprocessing
-pub const LEADING_DOCUMENTATION: u16 = 55;
-pub const SYNTH_DOCUMENT: u16 = 56;
-pub const TRAILING_DOCUMENTATION: u16 = 57;
+pub const LEADING_DOCUMENTATION: u16 = 56;
+pub const SYNTH_DOCUMENT: u16 = 57;
+pub const TRAILING_DOCUMENTATION: u16 = 58;
 
-pub const COUNT: u16 = 58;
+pub const COUNT: u16 = 59;
 
 // ---------------------------------------------------------------------------------------------------------------------
 
@@ -150,6 +151,7 @@ pub fn text_of_token_type(token_type: u16) -> &'static str {
         IDENTIFIER => "[identifier]",
 
         // Errors
+        INVALID_STRING_LITERAL => "[error - literal with invalid escape sequences]",
         UNCLOSED_DOUBLE_QUOTED_STRING => "[error - string extends past end of line]",
         UNCLOSED_SINGLE_QUOTED_STRING => "[error - literal extends past end of line]",
         UNRECOGNIZED_CHAR => "[error - unrecognized character]",
@@ -163,4 +165,92 @@ pub fn text_of_token_type(token_type: u16) -> &'static str {
     };
 }
 
+// ---------------------------------------------------------------------------------------------------------------------
+
+// Returns the language-neutral name of a token type, the same as the TypeScript TokenType where the two agree.
+pub fn tag_of_token_type(token_type: u16) -> &'static str {
+    return match token_type {
+        EOF => "#TokenTypeEof",
+
+        // Punctuation
+        AMPERSAND => "#TokenTypeAmpersand",
+        AMPERSAND_AMPERSAND => "#TokenTypeAmpersandAmpersand",
+        ASTERISK => "#TokenTypeAsterisk",
+        COLON => "#TokenTypeColon",
+        COMMA => "#TokenTypeComma",
+        DASH => "#TokenTypeDash",
+        DOT => "#TokenTypeDot",
+        DOT_DOT => "#TokenTypeDotDot",
+        DOT_DOT_DOT => "#TokenTypeDotDotDot",
+        EQUALS => "#TokenTypeEquals",
+        EQUALS_EQUALS => "#TokenTypeEqualsEquals",
+        EQUALS_EQUALS_EQUALS => "#TokenTypeEqualsEqualsEquals",
+        EQUALS_TILDE => "#TokenTypeEqualsTilde",
+        EXCLAMATION => "#TokenTypeExclamation",
+        EXCLAMATION_EQUALS => "#TokenTypeExclamationEquals",
+        EXCLAMATION_TILDE => "#TokenTypeExclamationTilde",
+        GREATER_THAN => "#TokenTypeGreaterThan",
+        GREATER_THAN_OR_EQUALS => "#TokenTypeGreaterThanOrEquals",
+        LEFT_BRACE => "#TokenTypeLeftBrace",
+        LEFT_BRACKET => "#TokenTypeLeftBracket",
+        LEFT_PARENTHESIS => "#TokenTypeLeftParenthesis",
+        LESS_THAN => "#TokenTypeLessThan",
+        LESS_THAN_OR_EQUALS => "#TokenTypeLessThanOrEquals",
+        PLUS => "#TokenTypePlus",
+        QUESTION => "#TokenTypeQuestion",
+        QUESTION_COLON => "#TokenTypeQuestionColon",
+        RIGHT_ARROW => "#TokenTypeRightArrow",
+        RIGHT_BRACE => "#TokenTypeRightBrace",
+        RIGHT_BRACKET => "#TokenTypeRightBracket",
+        RIGHT_PARENTHESIS => "#TokenTypeRightParenthesis",
+        SEMICOLON => "#TokenTypeSemicolon",
+        SLASH => "#TokenTypeSlash",
+        VERTICAL_BAR => "#TokenTypeVerticalBar",
+
+        // Keywords
+        AND => "#TokenTypeAnd",
+        AS => "#TokenTypeAs",
+        FALSE => "#TokenTypeFalse",
+        IN => "#TokenTypeIn",
+        IS => "#TokenTypeIs",
+        NOT => "#TokenTypeNot",
+        OR => "#TokenTypeOr",
+        TRUE => "#TokenTypeTrue",
+        WHEN => "#TokenTypeWhen",
+        WHERE => "#TokenTypeWhere",
+
+        // Literals
+        BACK_TICKED_STRING => "#TokenTypeBackTickedString",
+        BUILT_IN_TYPE => "#TokenTypeBuiltInType",
+        DOCUMENTATION => "#TokenTypeDocumentation",
+        DOUBLE_QUOTED_STRING => "#TokenTypeDoubleQuotedString",
+        FLOATING_POINT_LITERAL => "#TokenTypeFloatingPointLiteral",
+        INTEGER_LITERAL => "#TokenTypeIntegerLiteral",
+        SINGLE_QUOTED_STRING => "#TokenTypeSingleQuotedString",
+
+        // Identifiers
+        IDENTIFIER => "#TokenTypeIdentifier",
+
+        // Errors
+        INVALID_STRING_LITERAL => "#TokenTypeInvalidStringLiteral",
+        UNCLOSED_DOUBLE_QUOTED_STRING => "#TokenTypeUnclosedDoubleQuotedString",
+        UNCLOSED_SINGLE_QUOTED_STRING => "#TokenTypeUnclosedSingleQuotedString",
+        UNRECOGNIZED_CHAR => "#TokenTypeUnrecognizedChar",
+
+        // Synthetic token types from postprocessing
+        LEADING_DOCUMENTATION => "#TokenTypeLeadingDocumentation",
+        SYNTH_DOCUMENT => "#TokenTypeSynthDocument",
+        TRAILING_DOCUMENTATION => "#TokenTypeTrailingDocumentation",
+
+        _ => panic!("Missing token tag entry")
+    };
+}
+
+// ---------------------------------------------------------------------------------------------------------------------
+
+// Returns the token type with the given language-neutral name (see tag_of_token_type), if any.
+pub fn token_type_of_tag(tag: &str) -> Option<u16> {
+    return (0..COUNT).find(|&token_type| tag_of_token_type(token_type) == tag);
+}
+
 //=====================================================================================================================
@@ -7,25 +7,159 @@
 
 //=====================================================================================================================
 
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use serde::ser::SerializeStruct;
+use shared_vector::Vector;
+use crate::lligne::code::scanning::token_types::{tag_of_token_type, token_type_of_tag};
+
+//=====================================================================================================================
+
 // Token is an abstract token of type token_type occurring at source_offset with length source_length in its source code.
-#[derive(Clone, Copy)]
+// A token too long for source_length has LONG_TOKEN_LENGTH instead and its true length in a side table.
+// In JSON a token is {"sourceOffset": ..., "sourceLength": ..., "tokenType": "#TokenType..."}, naming its type by
+// tag_of_token_type so that the JSON does not depend on how token types are numbered. On its own a long token has
+// sourceLength LONG_TOKEN_LENGTH; the JSON of a whole scan outcome (see scanner::Outcome) has the true lengths.
+#[derive(Debug, Clone, Copy)]
 pub struct Token {
     pub source_offset: u32,
     pub source_length: u16,
     pub token_type: u16,
 }
 
+//---------------------------------------------------------------------------------------------------------------------
+
+impl Serialize for Token {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Token", 3)?;
+        state.serialize_field("sourceOffset", &self.source_offset)?;
+        state.serialize_field("sourceLength", &self.source_length)?;
+        state.serialize_field("tokenType", tag_of_token_type(self.token_type))?;
+        return state.end();
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// The JSON fields of a token before its tag is converted back to a token type.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenFields {
+    source_offset: u32,
+    source_length: u16,
+    token_type: String,
+}
+
+impl<'de> Deserialize<'de> for Token {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = TokenFields::deserialize(deserializer)?;
+
+        let token_type = token_type_of_tag(&fields.token_type)
+            .ok_or_else(|| D::Error::custom(format!("Unknown token type '{}'", fields.token_type)))?;
+
+        return Ok(Token {
+            source_offset: fields.source_offset,
+            source_length: fields.source_length,
+            token_type,
+        });
+    }
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// The source_length of a token whose length must be looked up in the table of long token lengths.
+pub const LONG_TOKEN_LENGTH: u16 = u16::MAX;
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// LongTokenLength records the length of the token at source_offset when it is too long for Token::source_length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LongTokenLength {
+    pub source_offset: u32,
+    pub source_length: u32,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Constructs a token, recording its length in long_token_lengths if it does not fit in the token itself. Long tokens
+// must be constructed in order of source offset.
+pub fn new_token(
+    source_offset: u32,
+    source_length: u32,
+    token_type: u16,
+    long_token_lengths: &mut Vector<LongTokenLength>,
+) -> Token {
+    if source_length < LONG_TOKEN_LENGTH as u32 {
+        return Token { source_offset, source_length: source_length as u16, token_type };
+    }
+
+    long_token_lengths.push(LongTokenLength { source_offset, source_length });
+
+    return Token { source_offset, source_length: LONG_TOKEN_LENGTH, token_type };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Returns the full length of a token, consulting the table of long token lengths (ordered by source offset) if need be.
+pub fn token_length(token: Token, long_token_lengths: &[LongTokenLength]) -> u32 {
+    if token.source_length != LONG_TOKEN_LENGTH {
+        return token.source_length as u32;
+    }
+
+    let index = long_token_lengths
+        .binary_search_by_key(&token.source_offset, |long_token_length| long_token_length.source_offset)
+        .expect("Long token missing from its table of lengths.");
+
+    return long_token_lengths[index].source_length;
+}
+
 //=====================================================================================================================
 
 #[cfg(test)]
 mod tests {
     use std::mem::size_of;
+    use crate::lligne::code::scanning::token_types;
     use super::*;
 
     #[test]
     fn test_token_size() {
         assert_eq!(8, size_of::<Token>());
     }
+
+    #[test]
+    fn test_token_json() {
+        let token = Token { source_offset: 12, source_length: 3, token_type: token_types::COLON };
+        let json = serde_json::to_string(&token).unwrap();
+
+        assert_eq!(r##"{"sourceOffset":12,"sourceLength":3,"tokenType":"#TokenTypeColon"}"##, json);
+
+        let round_trip: Token = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            (12, 3, token_types::COLON),
+            (round_trip.source_offset, round_trip.source_length, round_trip.token_type)
+        );
+
+        let unknown = serde_json::from_str::<Token>(r##"{"sourceOffset":0,"sourceLength":1,"tokenType":"#Nonsense"}"##);
+        assert!(unknown.is_err());
+    }
+
+    #[test]
+    fn test_long_token_lengths() {
+        let mut long_token_lengths: Vector<LongTokenLength> = Vector::new();
+
+        let short = new_token(10, 65534, 1, &mut long_token_lengths);
+        let long1 = new_token(70000, 65535, 2, &mut long_token_lengths);
+        let long2 = new_token(200000, 5000000, 3, &mut long_token_lengths);
+
+        assert_eq!(65534, short.source_length);
+        assert_eq!(LONG_TOKEN_LENGTH, long1.source_length);
+        assert_eq!(2, long_token_lengths.len());
+
+        assert_eq!(65534, token_length(short, &long_token_lengths));
+        assert_eq!(65535, token_length(long1, &long_token_lengths));
+        assert_eq!(5000000, token_length(long2, &long_token_lengths));
+        assert_eq!(3, long2.token_type);
+    }
 }
 
 //=====================================================================================================================
@@ -0,0 +1,173 @@
+//
+// # Golden (snapshot) tests of scanning, token filtering, and parsing.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+// Each Lligne source file tests/fixtures/NAME.llg has three snapshot files beside it:
+//
+//   - NAME.tokens      the tokens from the scanner, one per line
+//   - NAME.filtered    the tokens after the leading/trailing documentation filter
+//   - NAME.ast         the S-expression of the parsed source file followed by any diagnostics
+//
+// A snapshot that differs from the current output fails the test. To accept the current output (and to create the
+// snapshots for a new fixture), run the tests with LLIGNE_UPDATE_SNAPSHOTS=1 and review the changes as part of the
+// commit.
+//
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::lligne::code::parsing::parser;
+use crate::lligne::code::parsing::s_expressions::to_s_expression;
+use crate::lligne::code::scanning::{scanner, token_types};
+use crate::lligne::code::scanning::token_filters::leading_trailing_documentation;
+use crate::lligne::code::util::diagnostic_renderer::render_all_text;
+use crate::lligne::code::util::line_index::new_line_index;
+use crate::lligne::code::util::source_pos::new_source_pos;
+
+//=====================================================================================================================
+
+// Lists the tokens of a scan outcome, one per line giving source range, token type, and source text.
+fn tokens_snapshot(scan_result: &scanner::Outcome) -> String {
+    let mut result = String::new();
+
+    for token in scan_result.tokens.iter() {
+        let source_position = new_source_pos(*token, &scan_result.long_token_lengths);
+        let range = format!("{}..{}:", source_position.start_offset, source_position.end_offset);
+
+        result += &format!(
+            "{:<12}{:<36}{:?}\n",
+            range,
+            token_types::text_of_token_type(token.token_type),
+            source_position.get_text(scan_result.source_code)
+        );
+    }
+
+    return result;
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Prints the parsed source file followed by its diagnostics.
+fn ast_snapshot(file_name: &str, scan_result: &scanner::Outcome) -> String {
+    let parse_result = parser::parse_source_file(scan_result);
+
+    let mut result = to_s_expression(&parse_result.model(), scan_result.source_code);
+
+    if !parse_result.diagnostics.is_empty() {
+        let line_index = new_line_index(file_name, scan_result.source_code, scan_result.new_line_offsets.new_ref());
+        result += "\n";
+        result += &render_all_text(&parse_result.diagnostics, &line_index);
+    }
+
+    return result;
+}
+
+//=====================================================================================================================
+
+// Compares (or in update mode, writes) one snapshot file, returning a description of any mismatch.
+fn check_snapshot(snapshot_path: &Path, actual: &str, update: bool) -> Option<String> {
+    let expected = fs::read_to_string(snapshot_path).ok();
+
+    if expected.as_deref() == Some(actual) {
+        return None;
+    }
+
+    if update {
+        fs::write(snapshot_path, actual).unwrap();
+        return None;
+    }
+
+    return Some(match expected {
+        None => format!("Missing snapshot {}", snapshot_path.display()),
+        Some(expected) => format!(
+            "Snapshot {} differs:\n{}",
+            snapshot_path.display(),
+            line_differences(&expected, actual)
+        ),
+    });
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Describes the lines that differ between an expected and an actual snapshot.
+fn line_differences(expected: &str, actual: &str) -> String {
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+
+    let mut result = String::new();
+
+    for index in 0..expected_lines.len().max(actual_lines.len()) {
+        let expected_line = expected_lines.get(index);
+        let actual_line = actual_lines.get(index);
+
+        if expected_line != actual_line {
+            result += &format!("  line {}:\n", index + 1);
+            if let Some(line) = expected_line {
+                result += &format!("    - {}\n", line);
+            }
+            if let Some(line) = actual_line {
+                result += &format!("    + {}\n", line);
+            }
+        }
+    }
+
+    return result;
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Finds the Lligne source files in the fixtures directory in alphabetical order.
+fn fixture_paths() -> Vec<PathBuf> {
+    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures");
+
+    let mut result: Vec<PathBuf> = fs::read_dir(&fixtures_dir)
+        .unwrap_or_else(|_| panic!("Missing fixtures directory {}", fixtures_dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "llg"))
+        .collect();
+
+    result.sort();
+
+    return result;
+}
+
+//=====================================================================================================================
+
+#[test]
+fn test_snapshots() {
+    let update = std::env::var("LLIGNE_UPDATE_SNAPSHOTS").is_ok_and(|value| value == "1");
+
+    let mut failures: Vec<String> = vec![];
+
+    let fixture_paths = fixture_paths();
+    assert!(!fixture_paths.is_empty(), "No fixtures found.");
+
+    for fixture_path in fixture_paths {
+        let file_name = fixture_path.file_name().unwrap().to_string_lossy().to_string();
+        let source_code = fs::read_to_string(&fixture_path).unwrap();
+
+        let scan_result = scanner::scan(&source_code);
+        let filtered_scan_result = leading_trailing_documentation::filter(scanner::scan(&source_code));
+
+        let snapshots = [
+            ("tokens", tokens_snapshot(&scan_result)),
+            ("filtered", tokens_snapshot(&filtered_scan_result)),
+            ("ast", ast_snapshot(&file_name, &filtered_scan_result)),
+        ];
+
+        for (extension, actual) in snapshots {
+            if let Some(failure) = check_snapshot(&fixture_path.with_extension(extension), &actual, update) {
+                failures.push(failure);
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{}\nRun the tests with LLIGNE_UPDATE_SNAPSHOTS=1 to accept the current output.",
+        failures.join("\n")
+    );
+}
+
+//=====================================================================================================================
@@ -0,0 +1,278 @@
+//
+// # Rendering of diagnostics as annotated source snippets (for people) or JSON (for the IDE front end).
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+
+use serde_json::{json, Value};
+use crate::lligne::code::util::diagnostics::Diagnostic;
+use crate::lligne::code::util::line_index::{ColumnUnit, LineIndex};
+use crate::lligne::code::util::source_pos::SourcePos;
+
+//=====================================================================================================================
+
+// One underlined range of a source line: '^' for the primary position of a diagnostic, '-' for a secondary label.
+struct Annotation<'a> {
+    source_position: SourcePos,
+    line: u32,
+    marker: char,
+    message: &'a str,
+}
+
+//=====================================================================================================================
+
+// Renders a diagnostic as plain text in the style of rustc, e.g.
+//
+//   error[P1005]: Expected ',' or ')' but found end of file.
+//    --> example.llg:2:4
+//     |
+//   1 | f(a,
+//     |  - to match this '('
+//   2 |   b
+//     |    ^
+//
+pub fn render_text(diagnostic: &Diagnostic, line_index: &LineIndex) -> String {
+    let mut annotations = vec![Annotation {
+        source_position: diagnostic.source_position,
+        line: line_index.line_of(diagnostic.source_position.start_offset),
+        marker: '^',
+        message: "",
+    }];
+
+    for label in diagnostic.labels.iter() {
+        annotations.push(Annotation {
+            source_position: label.source_position,
+            line: line_index.line_of(label.source_position.start_offset),
+            marker: '-',
+            message: &label.message,
+        });
+    }
+
+    annotations.sort_by_key(|annotation| (annotation.line, annotation.source_position.start_offset));
+
+    let gutter_width = annotations.last().unwrap().line.to_string().len();
+    let gutter = " ".repeat(gutter_width);
+
+    let mut result = format!("{}[{}]: {}\n", diagnostic.severity.text(), diagnostic.code.id(), diagnostic.message);
+    result += &format!("{}--> {}\n", gutter, line_index.origin(diagnostic.source_position));
+    result += &format!("{} |\n", gutter);
+
+    let mut prior_line: Option<u32> = None;
+
+    for annotation in annotations.iter() {
+        if prior_line != Some(annotation.line) {
+            if prior_line.is_some_and(|prior_line| annotation.line > prior_line + 1) {
+                result += "...\n";
+            }
+
+            let line_text = line_index.line_text(annotation.line);
+            result += format!("{:>width$} | {}", annotation.line, line_text, width = gutter_width).trim_end();
+            result += "\n";
+            prior_line = Some(annotation.line);
+        }
+
+        let underline = format!("{} | {}", gutter, underline_text(annotation, line_index));
+        result += underline.trim_end();
+        result += "\n";
+    }
+
+    for help in diagnostic.help.iter() {
+        result += &format!("{} |\n{} = help: {}\n", gutter, gutter, help);
+    }
+
+    return result;
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Renders a sequence of diagnostics as plain text separated by blank lines.
+pub fn render_all_text(diagnostics: &[Diagnostic], line_index: &LineIndex) -> String {
+    return diagnostics.iter()
+        .map(|diagnostic| render_text(diagnostic, line_index))
+        .collect::<Vec<String>>()
+        .join("\n");
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Builds the indentation, markers, and message that underline one annotation within its line. The indentation copies
+// any tabs from the source line so that the markers line up beneath the annotated text.
+fn underline_text(annotation: &Annotation, line_index: &LineIndex) -> String {
+    let line_start_offset = line_index.line_start_offset(annotation.line);
+    let line_end_offset = line_index.line_end_offset(annotation.line);
+
+    let prefix = SourcePos {
+        start_offset: line_start_offset,
+        end_offset: annotation.source_position.start_offset,
+    };
+    let underlined = SourcePos {
+        start_offset: annotation.source_position.start_offset,
+        end_offset: annotation.source_position.end_offset.min(line_end_offset),
+    };
+
+    let indentation: String = prefix.get_text(line_index.source_code)
+        .chars()
+        .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+        .collect();
+    let marker_count = underlined.get_text(line_index.source_code).chars().count().max(1);
+    let markers = annotation.marker.to_string().repeat(marker_count);
+
+    return format!("{}{} {}", indentation, markers, annotation.message);
+}
+
+//=====================================================================================================================
+
+// Renders a diagnostic as JSON for the IDE front end. Positions give lines and columns (one-based, in bytes) for
+// display plus UTF-16 offsets from the start of the source code for the editor.
+pub fn render_json(diagnostic: &Diagnostic, line_index: &LineIndex) -> Value {
+    let labels: Vec<Value> = diagnostic.labels.iter()
+        .map(|label| json!({
+            "start": position_json(label.source_position.start_offset, line_index),
+            "end": position_json(label.source_position.end_offset, line_index),
+            "message": label.message,
+        }))
+        .collect();
+
+    let help: Vec<&String> = diagnostic.help.iter().collect();
+
+    return json!({
+        "severity": diagnostic.severity.text(),
+        "code": diagnostic.code.id(),
+        "message": diagnostic.message,
+        "file": line_index.file_name,
+        "start": position_json(diagnostic.source_position.start_offset, line_index),
+        "end": position_json(diagnostic.source_position.end_offset, line_index),
+        "text": diagnostic.source_position.get_text(line_index.source_code),
+        "labels": labels,
+        "help": help,
+    });
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Renders a sequence of diagnostics as a JSON array.
+pub fn render_all_json(diagnostics: &[Diagnostic], line_index: &LineIndex) -> Value {
+    return Value::Array(
+        diagnostics.iter()
+            .map(|diagnostic| render_json(diagnostic, line_index))
+            .collect()
+    );
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Describes one offset in JSON: its byte offset, line, column, and UTF-16 offset.
+pub fn position_json(offset: u32, line_index: &LineIndex) -> Value {
+    let line_col = line_index.line_col(offset);
+
+    return json!({
+        "offset": offset,
+        "line": line_col.line,
+        "column": line_col.column,
+        "utf16Offset": line_index.offset_to(offset, ColumnUnit::Utf16CodeUnits),
+    });
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::lligne::code::parsing::parser;
+    use crate::lligne::code::scanning::scanner;
+    use crate::lligne::code::util::line_index::new_line_index;
+    use super::*;
+
+    fn render(file_name: &str, source_code: &str) -> (String, Value) {
+        let scan_result = scanner::scan(source_code);
+        let parse_result = parser::parse_source_file(&scan_result);
+        let line_index = new_line_index(file_name, source_code, scan_result.new_line_offsets.new_ref());
+
+        return (
+            render_all_text(&parse_result.diagnostics, &line_index),
+            render_all_json(&parse_result.diagnostics, &line_index),
+        );
+    }
+
+    #[test]
+    fn test_render_text() {
+        let (text, _) = render("example.llg", "x = {\n  a: 1 +\n}");
+
+        assert_eq!(
+            "error[P1001]: Expected an expression but found '}'.\n\
+             \x20--> example.llg:3:1\n\
+             \x20 |\n\
+             3 | }\n\
+             \x20 | ^\n",
+            text
+        );
+    }
+
+    #[test]
+    fn test_render_text_with_label() {
+        let (text, _) = render("example.llg", "f(a,\n  b\n\n\nz = [1, 2\n");
+
+        assert_eq!(
+            "error[P1005]: Expected ',' or ')' but found 'z'.\n\
+             \x20--> example.llg:5:1\n\
+             \x20 |\n\
+             1 | f(a,\n\
+             \x20 |  - to match this '('\n\
+             ...\n\
+             5 | z = [1, 2\n\
+             \x20 | ^\n\
+             \n\
+             error[P1005]: Expected ')' but found end of file.\n\
+             \x20--> example.llg:6:1\n\
+             \x20 |\n\
+             1 | f(a,\n\
+             \x20 |  - to match this '('\n\
+             ...\n\
+             6 |\n\
+             \x20 | ^\n",
+            text
+        );
+    }
+
+    #[test]
+    fn test_render_text_with_help() {
+        let (text, _) = render("big.llg", "\tn = 99999999999999999999");
+
+        assert_eq!(
+            "error[P1013]: Integer literal is too large for a 64-bit integer.\n\
+             \x20--> big.llg:1:6\n\
+             \x20 |\n\
+             1 | \tn = 99999999999999999999\n\
+             \x20 | \t    ^^^^^^^^^^^^^^^^^^^^\n\
+             \x20 |\n\
+             \x20 = help: The largest integer literal is 9223372036854775807 (0x7FFF_FFFF_FFFF_FFFF).\n",
+            text
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let (_, json) = render("größe.llg", "größe = (1");
+
+        assert_eq!(
+            json!([{
+                "severity": "error",
+                "code": "P1005",
+                "message": "Expected ',' or ')' but found end of file.",
+                "file": "größe.llg",
+                "start": { "offset": 12, "line": 1, "column": 13, "utf16Offset": 10 },
+                "end": { "offset": 12, "line": 1, "column": 13, "utf16Offset": 10 },
+                "text": "",
+                "labels": [{
+                    "start": { "offset": 10, "line": 1, "column": 11, "utf16Offset": 8 },
+                    "end": { "offset": 11, "line": 1, "column": 12, "utf16Offset": 9 },
+                    "message": "to match this '('",
+                }],
+                "help": [],
+            }]),
+            json
+        );
+    }
+}
+
+//=====================================================================================================================
@@ -0,0 +1,167 @@
+//
+// # Diagnostics (errors and warnings) reported while processing Lligne code.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+
+use shared_vector::Vector;
+use crate::lligne::code::util::source_files::{FileId, FileSpan, new_file_span};
+use crate::lligne::code::util::source_pos::SourcePos;
+
+//=====================================================================================================================
+
+// The seriousness of a diagnostic.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl Severity {
+    // Returns the lower case name of the severity for use in messages.
+    pub fn text(&self) -> &'static str {
+        return match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Information => "information",
+        };
+    }
+}
+
+//=====================================================================================================================
+
+// Identifies the specific kind of problem reported by a diagnostic.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DiagnosticCode {
+    // Parsing
+    ExpectedExpression,
+    ExpectedEndOfInput,
+    ExpectedRightBrace,
+    ExpectedRightBracket,
+    ExpectedRightParenthesis,
+    FloatingPointLiteralOutOfRange,
+    InvalidEscapeSequence,
+    InvalidFloatingPointLiteral,
+    IntegerLiteralOutOfRange,
+    InvalidIntegerLiteral,
+    UnclosedCharacterLiteral,
+    UnclosedStringLiteral,
+    UnrecognizedCharacter,
+    UnsupportedPostfixOperator,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl DiagnosticCode {
+    // Returns the stable identifier of the code, e.g. "P1001".
+    pub fn id(&self) -> &'static str {
+        return match self {
+            DiagnosticCode::ExpectedExpression => "P1001",
+            DiagnosticCode::ExpectedEndOfInput => "P1002",
+            DiagnosticCode::ExpectedRightBrace => "P1003",
+            DiagnosticCode::ExpectedRightBracket => "P1004",
+            DiagnosticCode::ExpectedRightParenthesis => "P1005",
+            DiagnosticCode::InvalidFloatingPointLiteral => "P1006",
+            DiagnosticCode::InvalidIntegerLiteral => "P1007",
+            DiagnosticCode::UnclosedCharacterLiteral => "P1008",
+            DiagnosticCode::UnclosedStringLiteral => "P1009",
+            DiagnosticCode::UnrecognizedCharacter => "P1010",
+            DiagnosticCode::UnsupportedPostfixOperator => "P1011",
+            DiagnosticCode::InvalidEscapeSequence => "P1012",
+            DiagnosticCode::IntegerLiteralOutOfRange => "P1013",
+            DiagnosticCode::FloatingPointLiteralOutOfRange => "P1014",
+        };
+    }
+}
+
+//=====================================================================================================================
+
+// A secondary position related to a diagnostic, e.g. the opening bracket of an unclosed list, with a short message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub source_position: SourcePos,
+    pub message: String,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// A single problem found at a given position in the source code of a given file. Labels point at related positions
+// in the same file; help notes suggest how to fix the problem.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub file_id: FileId,
+    pub source_position: SourcePos,
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    pub message: String,
+    pub labels: Vector<Label>,
+    pub help: Vector<String>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Constructs an error diagnostic.
+pub fn new_error(file_id: FileId, source_position: SourcePos, code: DiagnosticCode, message: String) -> Diagnostic {
+    return Diagnostic {
+        file_id,
+        source_position,
+        severity: Severity::Error,
+        code,
+        message,
+        labels: Vector::new(),
+        help: Vector::new(),
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl Diagnostic {
+    // Returns the file and range of source code the diagnostic is about.
+    pub fn file_span(&self) -> FileSpan {
+        return new_file_span(self.file_id, self.source_position);
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Adds a label pointing at a related position.
+    pub fn with_label(mut self, source_position: SourcePos, message: &str) -> Diagnostic {
+        self.labels.push(Label { source_position, message: message.to_string() });
+        return self;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Adds a help note.
+    pub fn with_help(mut self, message: &str) -> Diagnostic {
+        self.help.push(message.to_string());
+        return self;
+    }
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_error() {
+        let diagnostic = new_error(
+            FileId(2),
+            SourcePos { start_offset: 3, end_offset: 4 },
+            DiagnosticCode::ExpectedExpression,
+            "Expected an expression.".to_string(),
+        );
+
+        assert_eq!(Severity::Error, diagnostic.severity);
+        assert_eq!("P1001", diagnostic.code.id());
+        assert_eq!("error", diagnostic.severity.text());
+        assert_eq!(FileId(2), diagnostic.file_span().file_id);
+    }
+}
+
+//=====================================================================================================================
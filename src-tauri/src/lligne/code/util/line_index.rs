@@ -0,0 +1,404 @@
+//
+// # Conversion of source offsets to and from line and column numbers.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+
+use std::fmt;
+use shared_vector::SharedVector;
+use crate::lligne::code::util::source_pos::SourcePos;
+
+//=====================================================================================================================
+
+// The units in which a column is counted: UTF-8 bytes (as in SourcePos), UTF-16 code units (as in editors and the
+// language server protocol), or Unicode scalar values (characters as a person would count them, barring combining
+// marks).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColumnUnit {
+    Utf8Bytes,
+    Utf16CodeUnits,
+    UnicodeScalars,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl ColumnUnit {
+    // Returns the number of units taken by the given text.
+    pub fn length_of(&self, text: &str) -> u32 {
+        return match self {
+            ColumnUnit::Utf8Bytes => text.len() as u32,
+            ColumnUnit::Utf16CodeUnits => text.encode_utf16().count() as u32,
+            ColumnUnit::UnicodeScalars => text.chars().count() as u32,
+        };
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the number of units taken by one character.
+    fn length_of_char(&self, ch: char) -> u32 {
+        return match self {
+            ColumnUnit::Utf8Bytes => ch.len_utf8() as u32,
+            ColumnUnit::Utf16CodeUnits => ch.len_utf16() as u32,
+            ColumnUnit::UnicodeScalars => 1,
+        };
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the byte length of the prefix of text taking the given number of units, or None if that count ends
+    // inside a character or beyond the end of the text.
+    fn byte_length_of_prefix(&self, text: &str, unit_count: u32) -> Option<u32> {
+        let mut units = 0;
+
+        for (byte_index, ch) in text.char_indices() {
+            if units == unit_count {
+                return Some(byte_index as u32);
+            }
+            if units > unit_count {
+                return None;
+            }
+            units += self.length_of_char(ch);
+        }
+
+        if units == unit_count {
+            return Some(text.len() as u32);
+        }
+
+        return None;
+    }
+}
+
+//=====================================================================================================================
+
+// A one-based line number and one-based column number. Unless stated otherwise, columns count bytes of UTF-8 from the
+// start of the line.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LineCol {
+    pub line: u32,
+    pub column: u32,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl fmt::Display for LineCol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}:{}", self.line, self.column);
+    }
+}
+
+//=====================================================================================================================
+
+// The human-readable location of a source position: its file plus the line and column where it starts and ends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    pub file_name: String,
+    pub start: LineCol,
+    pub end: LineCol,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}:{}", self.file_name, self.start);
+    }
+}
+
+//=====================================================================================================================
+
+// Maps byte offsets within one file of source code to lines and columns using the new line offsets from scanning.
+pub struct LineIndex<'a> {
+    pub file_name: &'a str,
+    pub source_code: &'a str,
+    new_line_offsets: SharedVector<u32>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Constructs a line index from the new line offsets found by the scanner for the given source code.
+pub fn new_line_index<'a>(
+    file_name: &'a str,
+    source_code: &'a str,
+    new_line_offsets: SharedVector<u32>,
+) -> LineIndex<'a> {
+    return LineIndex {
+        file_name,
+        source_code,
+        new_line_offsets,
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl<'a> LineIndex<'a> {
+    // Returns the number of lines in the source code; text after the last line feed counts as a line.
+    pub fn line_count(&self) -> u32 {
+        return self.new_line_offsets.len() as u32 + 1;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the one-based line containing the given byte offset. A line feed belongs to the line it ends.
+    pub fn line_of(&self, offset: u32) -> u32 {
+        return self.new_line_offsets.partition_point(|&new_line_offset| new_line_offset < offset) as u32 + 1;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the byte offset of the first character of the given one-based line.
+    pub fn line_start_offset(&self, line: u32) -> u32 {
+        if line <= 1 {
+            return 0;
+        }
+
+        return self.new_line_offsets[line as usize - 2] + 1;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the byte offset of the line feed ending the given one-based line, or the end of the source code.
+    pub fn line_end_offset(&self, line: u32) -> u32 {
+        return match self.new_line_offsets.get(line as usize - 1) {
+            Some(new_line_offset) => *new_line_offset,
+            None => self.source_code.len() as u32,
+        };
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Returns the text of the given one-based line without its line feed.
+    pub fn line_text(&self, line: u32) -> &'a str {
+        let text_range = self.line_start_offset(line) as usize..self.line_end_offset(line) as usize;
+        return &self.source_code[text_range];
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Converts a byte offset to its line and column.
+    pub fn line_col(&self, offset: u32) -> LineCol {
+        let line = self.line_of(offset);
+
+        return LineCol {
+            line,
+            column: offset - self.line_start_offset(line) + 1,
+        };
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Converts a line and column back to a byte offset. Returns None for a position outside the source code.
+    pub fn offset(&self, line_col: LineCol) -> Option<u32> {
+        if line_col.line < 1 || line_col.line > self.line_count() || line_col.column < 1 {
+            return None;
+        }
+
+        let offset = self.line_start_offset(line_col.line) + line_col.column - 1;
+
+        if offset > self.line_end_offset(line_col.line) {
+            return None;
+        }
+
+        return Some(offset);
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Determines the file, lines, and columns where a source position starts and ends.
+    pub fn origin(&self, source_position: SourcePos) -> Origin {
+        return Origin {
+            file_name: self.file_name.to_string(),
+            start: self.line_col(source_position.start_offset),
+            end: self.line_col(source_position.end_offset),
+        };
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Converts a start and end line and column back to a source position.
+    pub fn source_pos(&self, start: LineCol, end: LineCol) -> Option<SourcePos> {
+        let start_offset = self.offset(start)?;
+        let end_offset = self.offset(end)?;
+
+        if end_offset < start_offset {
+            return None;
+        }
+
+        return Some(SourcePos { start_offset, end_offset });
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Converts a byte offset, which must lie on a character boundary, to its line and a column counted in the given
+    // units.
+    pub fn line_col_in(&self, offset: u32, unit: ColumnUnit) -> LineCol {
+        let line = self.line_of(offset);
+        let line_prefix = &self.source_code[self.line_start_offset(line) as usize..offset as usize];
+
+        return LineCol {
+            line,
+            column: unit.length_of(line_prefix) + 1,
+        };
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Converts a line and a column counted in the given units back to a byte offset. Returns None for a position
+    // outside the source code or inside a character (e.g. between the two UTF-16 code units of an emoji).
+    pub fn offset_in(&self, line_col: LineCol, unit: ColumnUnit) -> Option<u32> {
+        if line_col.line < 1 || line_col.line > self.line_count() || line_col.column < 1 {
+            return None;
+        }
+
+        let byte_column = unit.byte_length_of_prefix(self.line_text(line_col.line), line_col.column - 1)?;
+
+        return Some(self.line_start_offset(line_col.line) + byte_column);
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Converts a byte offset, which must lie on a character boundary, to an offset from the start of the source code
+    // counted in the given units, e.g. a UTF-16 document offset for CodeMirror.
+    pub fn offset_to(&self, offset: u32, unit: ColumnUnit) -> u32 {
+        return unit.length_of(&self.source_code[..offset as usize]);
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Converts an offset from the start of the source code counted in the given units back to a byte offset. Returns
+    // None for an offset beyond the end of the source code or inside a character.
+    pub fn offset_from(&self, unit_offset: u32, unit: ColumnUnit) -> Option<u32> {
+        return unit.byte_length_of_prefix(self.source_code, unit_offset);
+    }
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::lligne::code::scanning::scanner;
+    use super::*;
+
+    fn line_col(line: u32, column: u32) -> LineCol {
+        return LineCol { line, column };
+    }
+
+    #[test]
+    fn test_line_col() {
+        let source_code = "abc\n  def\n\nghi";
+        let scan_result = scanner::scan(source_code);
+        let line_index = new_line_index("test.llg", source_code, scan_result.new_line_offsets);
+
+        assert_eq!(4, line_index.line_count());
+        assert_eq!(line_col(1, 1), line_index.line_col(0));
+        assert_eq!(line_col(1, 4), line_index.line_col(3));
+        assert_eq!(line_col(2, 1), line_index.line_col(4));
+        assert_eq!(line_col(2, 3), line_index.line_col(6));
+        assert_eq!(line_col(3, 1), line_index.line_col(10));
+        assert_eq!(line_col(4, 1), line_index.line_col(11));
+        assert_eq!(line_col(4, 4), line_index.line_col(14));
+
+        assert_eq!("  def", line_index.line_text(2));
+        assert_eq!("", line_index.line_text(3));
+        assert_eq!("ghi", line_index.line_text(4));
+    }
+
+    #[test]
+    fn test_offset() {
+        let source_code = "abc\n  def\n\nghi";
+        let scan_result = scanner::scan(source_code);
+        let line_index = new_line_index("test.llg", source_code, scan_result.new_line_offsets);
+
+        for offset in 0..=source_code.len() as u32 {
+            assert_eq!(Some(offset), line_index.offset(line_index.line_col(offset)));
+        }
+
+        assert_eq!(None, line_index.offset(line_col(0, 1)));
+        assert_eq!(None, line_index.offset(line_col(1, 0)));
+        assert_eq!(None, line_index.offset(line_col(1, 5)));
+        assert_eq!(None, line_index.offset(line_col(5, 1)));
+        assert_eq!(None, line_index.offset(line_col(4, 5)));
+    }
+
+    #[test]
+    fn test_origin() {
+        let source_code = "x = {\n  a: 1,\n  b: 2\n}\n";
+        let scan_result = scanner::scan(source_code);
+        let line_index = new_line_index("config.llg", source_code, scan_result.new_line_offsets);
+
+        let source_position = SourcePos { start_offset: 4, end_offset: 22 };
+        let origin = line_index.origin(source_position);
+
+        assert_eq!("config.llg", origin.file_name);
+        assert_eq!(line_col(1, 5), origin.start);
+        assert_eq!(line_col(4, 2), origin.end);
+        assert_eq!("config.llg:1:5", origin.to_string());
+
+        assert_eq!(Some(source_position), line_index.source_pos(origin.start, origin.end));
+        assert_eq!(None, line_index.source_pos(origin.end, origin.start));
+    }
+
+    #[test]
+    fn test_non_ascii_columns() {
+        let source_code = "größe = 'a😀b'\n  ß: \"x\"";
+        let scan_result = scanner::scan(source_code);
+        let line_index = new_line_index("test.llg", source_code, scan_result.new_line_offsets);
+
+        // The 'b' after the emoji.
+        let offset = source_code.find('b').unwrap() as u32;
+        assert_eq!(16, offset);
+        assert_eq!(line_col(1, 17), line_index.line_col_in(offset, ColumnUnit::Utf8Bytes));
+        assert_eq!(line_col(1, 13), line_index.line_col_in(offset, ColumnUnit::Utf16CodeUnits));
+        assert_eq!(line_col(1, 12), line_index.line_col_in(offset, ColumnUnit::UnicodeScalars));
+
+        // The '=' after the identifier.
+        assert_eq!(line_col(1, 7), line_index.line_col_in(8, ColumnUnit::Utf16CodeUnits));
+        assert_eq!(line_col(1, 9), line_index.line_col_in(8, ColumnUnit::Utf8Bytes));
+
+        // The ':' on the second line.
+        let offset = source_code.find(':').unwrap() as u32;
+        assert_eq!(line_col(2, 4), line_index.line_col_in(offset, ColumnUnit::Utf16CodeUnits));
+        assert_eq!(line_col(2, 5), line_index.line_col_in(offset, ColumnUnit::Utf8Bytes));
+
+        for unit in [ColumnUnit::Utf8Bytes, ColumnUnit::Utf16CodeUnits, ColumnUnit::UnicodeScalars] {
+            for (offset, _) in source_code.char_indices() {
+                let offset = offset as u32;
+                assert_eq!(Some(offset), line_index.offset_in(line_index.line_col_in(offset, unit), unit));
+                assert_eq!(Some(offset), line_index.offset_from(line_index.offset_to(offset, unit), unit));
+            }
+        }
+
+        assert_eq!(line_index.line_col(offset), line_index.line_col_in(offset, ColumnUnit::Utf8Bytes));
+    }
+
+    #[test]
+    fn test_offsets_inside_characters() {
+        let source_code = "'😀'";
+        let scan_result = scanner::scan(source_code);
+        let line_index = new_line_index("test.llg", source_code, scan_result.new_line_offsets);
+
+        assert_eq!(Some(1), line_index.offset_in(line_col(1, 2), ColumnUnit::Utf16CodeUnits));
+        assert_eq!(None, line_index.offset_in(line_col(1, 3), ColumnUnit::Utf16CodeUnits));
+        assert_eq!(Some(5), line_index.offset_in(line_col(1, 4), ColumnUnit::Utf16CodeUnits));
+        assert_eq!(Some(6), line_index.offset_in(line_col(1, 5), ColumnUnit::Utf16CodeUnits));
+        assert_eq!(None, line_index.offset_in(line_col(1, 6), ColumnUnit::Utf16CodeUnits));
+        assert_eq!(Some(5), line_index.offset_in(line_col(1, 3), ColumnUnit::UnicodeScalars));
+
+        assert_eq!(4, line_index.offset_to(6, ColumnUnit::Utf16CodeUnits));
+        assert_eq!(3, line_index.offset_to(6, ColumnUnit::UnicodeScalars));
+        assert_eq!(None, line_index.offset_from(2, ColumnUnit::Utf16CodeUnits));
+        assert_eq!(None, line_index.offset_from(5, ColumnUnit::Utf16CodeUnits));
+    }
+
+    #[test]
+    fn test_empty_source() {
+        let scan_result = scanner::scan("");
+        let line_index = new_line_index("empty.llg", "", scan_result.new_line_offsets);
+
+        assert_eq!(1, line_index.line_count());
+        assert_eq!(line_col(1, 1), line_index.line_col(0));
+        assert_eq!(Some(0), line_index.offset(line_col(1, 1)));
+    }
+}
+
+//=====================================================================================================================
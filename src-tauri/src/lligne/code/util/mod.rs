@@ -1,2 +1,5 @@
-
+pub mod diagnostic_renderer;
+pub mod diagnostics;
+pub mod line_index;
+pub mod source_files;
 pub mod source_pos;
@@ -0,0 +1,176 @@
+//
+// # Identities and contents of the source files loaded for processing.
+//
+// (C) Copyright 2023 Martin E. Nordberg III
+// Apache 2.0 License
+//
+
+use shared_vector::{SharedVector, Vector};
+use crate::lligne::code::util::line_index::{LineIndex, new_line_index, Origin};
+use crate::lligne::code::util::source_pos::SourcePos;
+
+//=====================================================================================================================
+
+// Identifies one source file within a source database.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(pub u32);
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// The file of source code scanned on its own rather than loaded into a source database, e.g. a REPL entry.
+pub const UNNAMED_FILE_ID: FileId = FileId(0);
+
+//=====================================================================================================================
+
+// A range of source code bytes within a given file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FileSpan {
+    pub file_id: FileId,
+    pub source_position: SourcePos,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Constructs a FileSpan instance.
+pub fn new_file_span(file_id: FileId, source_position: SourcePos) -> FileSpan {
+    return FileSpan {
+        file_id,
+        source_position,
+    };
+}
+
+//=====================================================================================================================
+
+// One loaded file: its name, its text, and the offsets of its line feeds.
+pub struct SourceFile {
+    pub file_id: FileId,
+    pub file_name: String,
+    pub source_code: String,
+    new_line_offsets: SharedVector<u32>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl SourceFile {
+    // Returns a line index for converting offsets within this file to lines and columns.
+    pub fn line_index(&self) -> LineIndex<'_> {
+        return new_line_index(&self.file_name, &self.source_code, self.new_line_offsets.new_ref());
+    }
+}
+
+//=====================================================================================================================
+
+// The collection of all source files loaded for processing, each assigned its own FileId.
+pub struct SourceDatabase {
+    files: Vector<SourceFile>,
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Constructs an empty source database.
+pub fn new_source_database() -> SourceDatabase {
+    return SourceDatabase {
+        files: Vector::new(),
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+impl SourceDatabase {
+    // Loads the text of a file, returning its newly assigned identity. File IDs start from one; zero is reserved for
+    // UNNAMED_FILE_ID.
+    pub fn add_file(&mut self, file_name: &str, source_code: String) -> FileId {
+        let file_id = FileId(self.files.len() as u32 + 1);
+
+        let mut new_line_offsets: Vector<u32> = Vector::new();
+        for (offset, _) in source_code.match_indices('\n') {
+            new_line_offsets.push(offset as u32);
+        }
+
+        self.files.push(SourceFile {
+            file_id,
+            file_name: file_name.to_string(),
+            source_code,
+            new_line_offsets: new_line_offsets.into_shared(),
+        });
+
+        return file_id;
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Looks up a loaded file by its identity.
+    pub fn file(&self, file_id: FileId) -> Option<&SourceFile> {
+        if file_id == UNNAMED_FILE_ID {
+            return None;
+        }
+
+        return self.files.get(file_id.0 as usize - 1);
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Looks up a loaded file by its name.
+    pub fn file_named(&self, file_name: &str) -> Option<&SourceFile> {
+        return self.files.iter().find(|file| file.file_name == file_name);
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Slices the source code of its file to produce the text of a file span.
+    pub fn get_text(&self, file_span: FileSpan) -> Option<&str> {
+        return self.file(file_span.file_id)
+            .map(|file| file_span.source_position.get_text(&file.source_code));
+    }
+
+    //-----------------------------------------------------------------------------------------------------------------
+
+    // Determines the file name, lines, and columns of a file span.
+    pub fn origin(&self, file_span: FileSpan) -> Option<Origin> {
+        return self.file(file_span.file_id)
+            .map(|file| file.line_index().origin(file_span.source_position));
+    }
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::mem::size_of;
+    use crate::lligne::code::util::line_index::LineCol;
+    use super::*;
+
+    #[test]
+    fn test_file_span_size() {
+        assert_eq!(12, size_of::<FileSpan>());
+    }
+
+    #[test]
+    fn test_source_database() {
+        let mut source_database = new_source_database();
+
+        let file_id_a = source_database.add_file("a.llg", "x = 1\ny = 2\n".to_string());
+        let file_id_b = source_database.add_file("b.llg", "z = 3".to_string());
+
+        assert_eq!(FileId(1), file_id_a);
+        assert_eq!(FileId(2), file_id_b);
+        assert!(source_database.file(UNNAMED_FILE_ID).is_none());
+        assert!(source_database.file(FileId(3)).is_none());
+        assert_eq!(file_id_b, source_database.file_named("b.llg").unwrap().file_id);
+
+        let span_a = new_file_span(file_id_a, SourcePos { start_offset: 6, end_offset: 11 });
+        let span_b = new_file_span(file_id_b, SourcePos { start_offset: 4, end_offset: 5 });
+
+        assert_eq!(Some("y = 2"), source_database.get_text(span_a));
+        assert_eq!(Some("3"), source_database.get_text(span_b));
+
+        let origin = source_database.origin(span_a).unwrap();
+        assert_eq!("a.llg", origin.file_name);
+        assert_eq!(LineCol { line: 2, column: 1 }, origin.start);
+        assert_eq!(LineCol { line: 2, column: 6 }, origin.end);
+
+        assert_eq!("b.llg:1:5", source_database.origin(span_b).unwrap().to_string());
+    }
+}
+
+//=====================================================================================================================
@@ -3,12 +3,15 @@
 // Apache 2.0 License
 //
 
-use crate::lligne::code::scanning::tokens::Token;
+use serde::{Deserialize, Serialize};
+use crate::lligne::code::scanning::tokens::{LongTokenLength, Token, token_length};
 
 //=====================================================================================================================
 
-// Represents a range of source code bytes from start_offset to end_offset.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+// Represents a range of source code bytes from start_offset to end_offset. In JSON it is
+// {"startOffset": ..., "endOffset": ...}.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SourcePos {
     pub start_offset: u32,
     pub end_offset: u32,
@@ -16,11 +19,11 @@ pub struct SourcePos {
 
 //---------------------------------------------------------------------------------------------------------------------
 
-// Constructs a SourcePos instance.
-pub fn new_source_pos(token: Token) -> SourcePos {
+// Constructs a SourcePos instance covering the given token, whose length may be in the table of long token lengths.
+pub fn new_source_pos(token: Token, long_token_lengths: &[LongTokenLength]) -> SourcePos {
     return SourcePos {
         start_offset: token.source_offset,
-        end_offset: token.source_offset + token.source_length as u32,
+        end_offset: token.source_offset + token_length(token, long_token_lengths),
     };
 }
 
@@ -75,30 +78,3 @@ mod tests {
 }
 
 //=====================================================================================================================
-
-//func (t *LligneTokenOriginTracker) GetOrigin(sourcePos int) LligneOrigin {
-//
-//	priorNewLinePos := 0
-//	if len(t.newLinePositions) > 0 {
-//		iMin := 0
-//		iMax := len(t.newLinePositions)
-//		for iMax-iMin > 1 {
-//			iMid := (iMin + iMax) / 2
-//			if sourcePos > t.newLinePositions[iMid] {
-//				iMin = iMid
-//			} else {
-//				iMax = iMid
-//			}
-//		}
-//		priorNewLinePos = iMin
-//	}
-//
-//	return LligneOrigin{
-//		FileName: t.fileName,
-//		Line:     priorNewLinePos + 1,
-//		Column:   sourcePos - t.newLinePositions[priorNewLinePos],
-//	}
-//
-//}
-
-//=====================================================================================================================
@@ -0,0 +1,174 @@
+use serde_json::{json, Value};
+use crate::lligne::code::formatting::formatter::{format_source_file, FormatOptions};
+use crate::lligne::code::formatting::text_edits;
+use crate::lligne::code::formatting::text_edits::TextEdit;
+use crate::lligne::code::scanning::scanner;
+use crate::lligne::code::util::diagnostic_renderer::{position_json, render_all_json};
+use crate::lligne::code::util::diagnostics::Diagnostic;
+use crate::lligne::code::util::line_index::{ColumnUnit, LineIndex, new_line_index};
+use crate::lligne::code::util::source_pos::SourcePos;
+
+// Formats the code in an editor pane (the IDE's `lligne fmt`), returning the formatted code, or null with the
+// diagnostics if the code has syntax errors, as JSON, e.g.
+//
+//   { "formatted": "x: 1,\ny: 2\n",
+//     "diagnostics": [] }
+//
+// The line width defaults to 100 columns.
+#[tauri::command]
+pub fn format_from_editor(source_code: &str, line_width: Option<usize>) -> Value {
+    let outcome = format_source_file(source_code, &format_options(line_width));
+
+    let line_index = new_line_index("editor", source_code, outcome.new_line_offsets.new_ref());
+
+    return json!({
+        "formatted": outcome.formatted,
+        "diagnostics": render_all_json(&outcome.diagnostics, &line_index),
+    });
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Formats the selected range of an editor pane, given by UTF-16 offsets, returning the edits that do so (none if the
+// code has syntax errors) with any diagnostics as JSON, e.g.
+//
+//   { "edits": [ { "start": {...}, "end": {...}, "newText": " " } ],
+//     "diagnostics": [] }
+//
+// Each edit replaces the text from its start position through its end position (each an offset, line, column, and
+// UTF-16 offset) with its new text. The edits are in source order and apply to the unedited code.
+#[tauri::command]
+pub fn format_range_from_editor(
+    source_code: &str,
+    start_utf16_offset: u32,
+    end_utf16_offset: u32,
+    line_width: Option<usize>,
+) -> Value {
+    let line_index = editor_line_index(source_code);
+    let range = SourcePos {
+        start_offset: line_index.offset_from(start_utf16_offset, ColumnUnit::Utf16CodeUnits).unwrap_or(0),
+        end_offset: line_index.offset_from(end_utf16_offset, ColumnUnit::Utf16CodeUnits)
+            .unwrap_or(source_code.len() as u32),
+    };
+
+    let outcome = text_edits::format_range(source_code, range, &format_options(line_width));
+
+    return edits_json(&outcome.edits, &outcome.diagnostics, &line_index);
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Formats the record or list around a '}' or ',' just typed at the given UTF-16 offset of an editor pane, returning
+// the edits as for format_range_from_editor.
+#[tauri::command]
+pub fn format_on_type_from_editor(
+    source_code: &str,
+    utf16_offset: u32,
+    typed: char,
+    line_width: Option<usize>,
+) -> Value {
+    let line_index = editor_line_index(source_code);
+    let offset = line_index.offset_from(utf16_offset, ColumnUnit::Utf16CodeUnits).unwrap_or(0);
+
+    let outcome = text_edits::format_on_type(source_code, offset, typed, &format_options(line_width));
+
+    return edits_json(&outcome.edits, &outcome.diagnostics, &line_index);
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Builds the options for formatting with the given line width (by default 100 columns).
+fn format_options(line_width: Option<usize>) -> FormatOptions {
+    return FormatOptions {
+        line_width: line_width.unwrap_or(FormatOptions::default().line_width),
+        ..FormatOptions::default()
+    };
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Indexes the lines of the code in an editor pane.
+fn editor_line_index(source_code: &str) -> LineIndex<'_> {
+    return new_line_index("editor", source_code, scanner::scan(source_code).new_line_offsets);
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Converts edits and diagnostics to JSON.
+fn edits_json(edits: &[TextEdit], diagnostics: &[Diagnostic], line_index: &LineIndex) -> Value {
+    let edits: Vec<Value> = edits.iter()
+        .map(|edit| json!({
+            "start": position_json(edit.source_position.start_offset, line_index),
+            "end": position_json(edit.source_position.end_offset, line_index),
+            "newText": edit.new_text,
+        }))
+        .collect();
+
+    return json!({
+        "edits": edits,
+        "diagnostics": render_all_json(diagnostics, line_index),
+    });
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_editor() {
+        assert_eq!(
+            json!({ "formatted": "x: 1,\ny: {a: 2}\n", "diagnostics": [] }),
+            format_from_editor("x:1, y:{ a:2 }", None)
+        );
+        assert_eq!(
+            json!({ "formatted": "x: 1,\ny: {\n    a: 2,\n}\n", "diagnostics": [] }),
+            format_from_editor("x:1, y:{ a:2 }", Some(6))
+        );
+    }
+
+    #[test]
+    fn test_format_from_editor_with_errors() {
+        let outcome = format_from_editor("x: (1", None);
+
+        assert_eq!(Value::Null, outcome["formatted"]);
+        assert_eq!("P1005", outcome["diagnostics"][0]["code"]);
+    }
+
+    #[test]
+    fn test_format_range_from_editor() {
+        let outcome = format_range_from_editor("a = 'é'+1,\nb = 2+3", 0, 9, None);
+
+        assert_eq!(
+            json!({
+                "edits": [
+                    {
+                        "start": { "offset": 8, "line": 1, "column": 9, "utf16Offset": 7 },
+                        "end": { "offset": 8, "line": 1, "column": 9, "utf16Offset": 7 },
+                        "newText": " ",
+                    },
+                    {
+                        "start": { "offset": 9, "line": 1, "column": 10, "utf16Offset": 8 },
+                        "end": { "offset": 9, "line": 1, "column": 10, "utf16Offset": 8 },
+                        "newText": " ",
+                    },
+                ],
+                "diagnostics": [],
+            }),
+            outcome
+        );
+    }
+
+    #[test]
+    fn test_format_on_type_from_editor() {
+        let outcome = format_on_type_from_editor("x = 1+2,\ny = {a:1}", 17, '}', None);
+
+        let edits = outcome["edits"].as_array().unwrap();
+        assert_eq!(1, edits.len());
+        assert_eq!(16, edits[0]["start"]["offset"]);
+        assert_eq!(" ", edits[0]["newText"]);
+    }
+}
+
+//=====================================================================================================================
@@ -1,19 +1,119 @@
+use serde_json::{json, Value};
 use crate::lligne::code::parsing::parser;
 use crate::lligne::code::scanning::scanner;
 use crate::lligne::code::scanning::token_filters::leading_trailing_documentation;
+use crate::lligne::code::util::diagnostic_renderer::{position_json, render_all_json};
+use crate::lligne::code::util::line_index::{LineIndex, new_line_index};
 
+// Parses the code entered in the REPL, returning the expression tree and any diagnostics as JSON, e.g.
+//
+//   { "model": { "tag": "#AdditionExpr", "sourcePos": {...}, "start": {...}, "end": {...}, "lhs": {...}, ... },
+//     "diagnostics": [...],
+//     "lineCount": 1 }
+//
+// The tree has the same shape as the serialized Expr (see expressions_serde), with each node also given its start and
+// end positions (offset, line, column, and UTF-16 offset) alongside its "sourcePos".
 #[tauri::command]
-pub fn parse_from_repl(source_code: &str) -> String {
-    println!("parse_from_source_code");
-
+pub fn parse_from_repl(source_code: &str) -> Value {
     let mut scan_result = scanner::scan(source_code);
 
     scan_result = leading_trailing_documentation::filter(scan_result);
 
     let expression = parser::parse_expression(&scan_result);
 
-    assert!(expression.model.get_source_position().start_offset <
-        expression.model.get_source_position().end_offset);
+    let line_index = new_line_index("repl", source_code, expression.new_line_offsets.new_ref());
+
+    let mut model = serde_json::to_value(&*expression.model()).unwrap();
+    add_positions(&mut model, &line_index);
+
+    return json!({
+        "model": model,
+        "diagnostics": render_all_json(&expression.diagnostics, &line_index),
+        "lineCount": line_index.line_count(),
+    });
+}
+
+//---------------------------------------------------------------------------------------------------------------------
+
+// Adds the start and end positions of each node (recursively) of a serialized expression tree next to its sourcePos.
+fn add_positions(value: &mut Value, line_index: &LineIndex) {
+    match value {
+        Value::Object(fields) => {
+            if let Some(source_position) = fields.get("sourcePos") {
+                let offset_of = |key: &str| source_position[key].as_u64().unwrap() as u32;
+                let start = position_json(offset_of("startOffset"), line_index);
+                let end = position_json(offset_of("endOffset"), line_index);
+
+                fields.insert("start".to_string(), start);
+                fields.insert("end".to_string(), end);
+            }
+
+            for field in fields.values_mut() {
+                add_positions(field, line_index);
+            }
+        }
+        Value::Array(elements) => {
+            for element in elements {
+                add_positions(element, line_index);
+            }
+        }
+        _ => {}
+    }
+}
+
+//=====================================================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_from_repl() {
+        let outcome = parse_from_repl("x +\n  \"größe\"");
 
-    return format!("Parsed");
+        assert_eq!(
+            json!({
+                "model": {
+                    "tag": "#AdditionExpr",
+                    "sourcePos": { "startOffset": 0, "endOffset": 15 },
+                    "start": { "offset": 0, "line": 1, "column": 1, "utf16Offset": 0 },
+                    "end": { "offset": 15, "line": 2, "column": 12, "utf16Offset": 13 },
+                    "lhs": {
+                        "tag": "#IdentifierExpr",
+                        "sourcePos": { "startOffset": 0, "endOffset": 1 },
+                        "start": { "offset": 0, "line": 1, "column": 1, "utf16Offset": 0 },
+                        "end": { "offset": 1, "line": 1, "column": 2, "utf16Offset": 1 },
+                    },
+                    "rhs": {
+                        "tag": "#DoubleQuotedStringExpr",
+                        "sourcePos": { "startOffset": 6, "endOffset": 15 },
+                        "start": { "offset": 6, "line": 2, "column": 3, "utf16Offset": 6 },
+                        "end": { "offset": 15, "line": 2, "column": 12, "utf16Offset": 13 },
+                        "value": "größe",
+                    },
+                },
+                "diagnostics": [],
+                "lineCount": 2,
+            }),
+            outcome
+        );
+    }
+
+    #[test]
+    fn test_parse_from_repl_with_errors() {
+        let outcome = parse_from_repl("[1, 2");
+
+        assert_eq!("#ArrayLiteralExpr", outcome["model"]["tag"]);
+        assert_eq!(2, outcome["model"]["elements"].as_array().unwrap().len());
+        assert_eq!(2, outcome["model"]["elements"][1]["value"]);
+        assert_eq!(6, outcome["model"]["end"]["column"]);
+
+        let diagnostics = outcome["diagnostics"].as_array().unwrap();
+        assert_eq!(1, diagnostics.len());
+        assert_eq!("P1004", diagnostics[0]["code"]);
+        assert_eq!("repl", diagnostics[0]["file"]);
+        assert_eq!(5, diagnostics[0]["start"]["offset"]);
+    }
 }
+
+//=====================================================================================================================
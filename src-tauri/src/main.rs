@@ -11,10 +11,14 @@ fn greet(name: &str) -> String {
 
 
 fn main() {
+    use lligne::ide::formatting::{format_from_editor, format_on_type_from_editor, format_range_from_editor};
     use lligne::ide::repl::parse_from_repl;
     tauri::Builder::default()
         .invoke_handler(tauri::generate_handler![
             greet,
+            format_from_editor,
+            format_on_type_from_editor,
+            format_range_from_editor,
             parse_from_repl
         ])
         .run(tauri::generate_context!())